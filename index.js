@@ -1,6 +1,11 @@
 import * as wasm from './pkg';
 
-let permenTerpilih = [];
+// Jarak minimal dalam piksel agar tarikan dianggap geseran dan bukan ketukan
+const JARAK_GESER = 20;
+
+// Kunci localStorage untuk catatan kampanye
+const KUNCI_KAMPANYE = "catatanKampanye";
+
 let score = 0;
 
 let petunjukBtn = document.getElementById("petunjuk");
@@ -17,122 +22,169 @@ petunjukBtn.addEventListener("click", e => {
   }
 })
 
+// Susunan kode papan, lihat Papan::kode
+const KODE_PER_PETAK = 7;
+const KODE_TANPA_PERMEN = 255;
+const PETAK_PENGHALANG = 2;
+const PETAK_COKELAT = 3;
+const JENIS_BAHAN = 4;
+const JENIS_PELANGI = 5;
+const TANDA_KUNCI = 1;
+const TANDA_KELUAR = 2;
+
+// Tampilan untuk setiap indeks warna: merah, jingga, kuning, hijau, biru, nila, ungu
+const TAMPILAN_WARNA = [
+  ["red", "fa-hamburger"],
+  ["orange", "fa-cheese"],
+  ["hotpink", "fa-apple-alt"],
+  ["limegreen", "fa-pizza-slice"],
+  ["deepskyblue", "fa-beer"],
+  ["brown", "fa-cookie"],
+  ["purple", "fa-ice-cream"],
+];
+
+// Kelas tambahan untuk setiap kode jenis permen
+const KELAS_JENIS = ["", "garis-mendatar", "garis-tegak", "bungkus"];
+
 async function main() {
-  renderPapan(wasm.buatPapan());
+  try {
+    wasm.muatCatatanKampanye(localStorage.getItem(KUNCI_KAMPANYE));
+  } catch (galat) {
+    console.warn("catatan kampanye tidak bisa dimuat:", galat);
+  }
+  renderPapan(wasm.kodePapan());
+}
+
+// Mencatat level kampanye yang baru selesai dan menyimpan catatannya, tidak melakukan
+// apa pun jika tidak ada level kampanye yang sedang dimainkan atau levelnya belum selesai
+function simpanKampanye() {
+  try {
+    wasm.selesaikanLevel();
+  } catch (_) {
+    return;
+  }
+  localStorage.setItem(KUNCI_KAMPANYE, wasm.catatanKampanye());
 }
 
-function renderPapan(state) {
+// Menampilkan hasil ketukan atau geseran: pilihan baru, atau papan setelah giliran dimainkan
+function terapkanTanggapan(tanggapan) {
+  if (tanggapan.main) {
+    score = tanggapan.main.skorTotal;
+    renderPapan(tanggapan.main.papan);
+    return;
+  }
+  document.querySelectorAll(".permen.glow").forEach(elm => elm.classList.remove("glow"));
+  if (tanggapan.terpilih) {
+    let [y, x] = tanggapan.terpilih;
+    document.getElementById(`permen${y}${x}`).classList.add("glow");
+  }
+  tampilkanKursor(tanggapan.kursor);
+}
+
+function tampilkanKursor([y, x]) {
+  document.querySelectorAll(".permen.kursor").forEach(elm => elm.classList.remove("kursor"));
+  document.getElementById(`permen${y}${x}`).classList.add("kursor");
+}
+
+// Elemen yang memakai tombolnya sendiri, tombol yang ditekan di sini bukan untuk papan
+const ELEMEN_INTERAKTIF = "a[href], input, textarea, select, button, [contenteditable]";
+
+// Bermain dengan tombol panah, Enter, dan Escape
+document.addEventListener("keydown", e => {
+  if (e.target instanceof Element && e.target.closest(ELEMEN_INTERAKTIF)) { return }
+  let tanggapan = wasm.tekanTombol(e.key);
+  if (tanggapan) {
+    e.preventDefault();
+    terapkanTanggapan(tanggapan);
+  }
+});
+
+function renderPapan(kode) {
   let scoreCounter = document.getElementById("score");
   let app = document.getElementById("app");
   app.innerHTML = '';
 
-  let data = state.split("\n").map((row) => row.trim().split(/\s+/));
-  data.pop();  // remove the last row because it is empty
+  let [lebar, tinggi] = kode;
 
   app.style.display = "grid";
-  app.style.gridTemplate = `repeat(${data.length}, auto) / repeat(${data[0].length}, auto)`;
+  app.style.gridTemplate = `repeat(${tinggi}, auto) / repeat(${lebar}, auto)`;
+
+  for (let y = 0; y < tinggi; y++) {
+    for (let x = 0; x < lebar; x++) {
+      let i = 2 + (y * lebar + x) * KODE_PER_PETAK;
+      let [petak, nilai, warna, jenis, jeli, tanda, bom] = kode.slice(i, i + KODE_PER_PETAK);
 
-  data.forEach((row, y) => {
-    row.forEach((cell, x) => {
       let elm = document.createElement("a");
       elm.setAttribute("id", `permen${y}${x}`);
       elm.classList.add("permen", "m-1", "btn");
+      if (jeli > 0) { elm.classList.add(`jeli-${jeli}`) }
+      if (tanda & TANDA_KUNCI) { elm.classList.add("kunci") }
+      if (tanda & TANDA_KELUAR) { elm.classList.add("keluar") }
 
       let childElm = document.createElement("i");
       childElm.style.color = "white";
 
-      switch (cell) {
-        case "🟥":
-          elm.style.backgroundColor = "red";
-          childElm.classList.add("fas", "fa-hamburger");
-          break;
-        case "🟧":
-          elm.style.backgroundColor = "orange";
-          childElm.classList.add("fas", "fa-cheese");
-          break;
-        case "🟨":
-          elm.style.backgroundColor = "hotpink";
-          childElm.classList.add("fas", "fa-apple-alt");
-          break;
-        case "🟩":
-          elm.style.backgroundColor = "limegreen";
-          childElm.classList.add("fas", "fa-pizza-slice");
-          break;
-        case "🟦":
-          elm.style.backgroundColor = "deepskyblue";
-          childElm.classList.add("fas", "fa-beer");
-          break;
-        case "🟫":
-          elm.style.backgroundColor = "brown";
-          childElm.classList.add("fas", "fa-cookie");
-          break;
-        case "🟪":
-          elm.style.backgroundColor = "purple";
-          childElm.classList.add("fas", "fa-ice-cream");
-          break;
-
-        default:
-          elm.style.backgroundColor = "white";
-          childElm.classList.add("fas", "fa-times");
-          break;
+      if (warna != KODE_TANPA_PERMEN && jenis == JENIS_BAHAN) {
+        elm.style.backgroundColor = "white";
+        childElm.style.color = "crimson";
+        childElm.classList.add("fas", "fa-seedling");
+      } else if (warna != KODE_TANPA_PERMEN && jenis == JENIS_PELANGI) {
+        elm.style.background = "linear-gradient(135deg, red, orange, gold, limegreen, deepskyblue, purple)";
+        childElm.classList.add("fas", "fa-star");
+      } else if (warna != KODE_TANPA_PERMEN) {
+        let [latar, ikon] = TAMPILAN_WARNA[warna];
+        elm.style.backgroundColor = latar;
+        childElm.classList.add("fas", ikon);
+        if (KELAS_JENIS[jenis]) { elm.classList.add(KELAS_JENIS[jenis]) }
+        if (bom > 0) {
+          elm.classList.add("bom");
+          elm.dataset.bom = bom;
+        }
+      } else if (petak == PETAK_PENGHALANG) {
+        elm.style.backgroundColor = "lightgray";
+        childElm.innerText = nilai;
+      } else if (petak == PETAK_COKELAT) {
+        elm.style.backgroundColor = "saddlebrown";
+        childElm.classList.add("fas", "fa-cookie");
+      } else {
+        elm.style.backgroundColor = "white";
+        childElm.classList.add("fas", "fa-times");
       }
 
       elm.append(childElm);
 
+      let awal = null;
+      let digeser = false;
+      elm.addEventListener("pointerdown", e => {
+        awal = [e.clientX, e.clientY];
+        digeser = false;
+        elm.setPointerCapture(e.pointerId);
+      });
+      elm.addEventListener("pointerup", e => {
+        if (!awal) { return }
+        let [dx, dy] = [e.clientX - awal[0], e.clientY - awal[1]];
+        awal = null;
+        if (Math.max(Math.abs(dx), Math.abs(dy)) < JARAK_GESER) { return }
+        digeser = true;
+        let arah = Math.abs(dx) > Math.abs(dy) ? (dx > 0 ? "kanan" : "kiri") : (dy > 0 ? "bawah" : "atas");
+        terapkanTanggapan(wasm.geserPermen(y, x, arah));
+      });
       elm.addEventListener("click", e => {
         e.preventDefault();
-
-        let pos = `${y} ${x}`;
-        // Menambahkan posisi ke permen terpilih
-        if (permenTerpilih.indexOf(pos) < 0 && permenTerpilih.length < 3) {
-          if (permenTerpilih.length == 1) {
-            let [a, b] = permenTerpilih[0].split(" ").map(p => parseInt(p));
-            if (Math.abs((a+b)-(y+x)) == 1) {
-              permenTerpilih.push(pos);
-              elm.classList.add("glow");
-            }
-          } else if (permenTerpilih.length == 0) {
-            permenTerpilih.push(pos);
-            elm.classList.add("glow");
-          }
-        } else {
-          let idx = permenTerpilih.indexOf(pos);
-          if (idx > -1) { permenTerpilih.splice(idx, 1) }
-          elm.classList.remove("glow");
-        }
-
-        // Tukar posisi dan render kembali papan
-        if (permenTerpilih.length == 2) {
-          let [y1, x1] = permenTerpilih[0].split(" ").map(p => parseInt(p));
-          let [y2, x2] = permenTerpilih[1].split(" ").map(p => parseInt(p));
-          wasm.tukarPosisi(y1, x1, y2, x2);
-          renderPapan(wasm.buatPapan());
-          wasm.cekDempet(y1, x1, 3);
-          wasm.cekDempet(y2, x2, 3);
-          permenTerpilih = [];
-
-          if (wasm.panjangDempet() == 0) {
-            wasm.tukarPosisi(y1, x1, y2, x2);
-            renderPapan(wasm.buatPapan());
-          }
-        }
-
-        if (wasm.panjangDempet() > 0) {
-          score += wasm.panjangDempet();
-          wasm.remukanPermen();
-          renderPapan(wasm.buatPapan());
-        }
-
+        if (digeser) { return }
+        terapkanTanggapan(wasm.ketukPermen(y, x));
       });
 
       app.appendChild(elm);
-      scoreCounter.innerText = score;
-    });
-  });
+    }
+  }
+  scoreCounter.innerText = score;
+  tampilkanKursor(wasm.kursorPapan());
 
   if (!wasm.bisaJalan()) {
     scoreCounter.innerText += "   ⛔ Game Over ⛔"
   }
+  simpanKampanye();
 }
 
 main();
@@ -0,0 +1,273 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::level::{Level, LEVEL_BAWAAN};
+use crate::permenremuk::*;
+
+/// Syarat agar sebuah level kampanye bisa dimainkan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyaratBuka {
+    Bebas,
+    LulusSebelumnya,
+    TotalBintang(u32),
+}
+
+/// Tempat menyimpan catatan kampanye dalam bentuk string
+pub trait Penyimpanan {
+    fn muat(&self) -> Option<String>;
+    fn simpan(&mut self, isi: String);
+}
+
+/// Penyimpanan di memori, dipakai untuk pengujian
+#[derive(Debug, Clone, Default)]
+pub struct PenyimpananMemori {
+    pub isi: Option<String>,
+}
+
+impl Penyimpanan for PenyimpananMemori {
+    fn muat(&self) -> Option<String> {
+        self.isi.clone()
+    }
+
+    fn simpan(&mut self, isi: String) {
+        self.isi = Some(isi);
+    }
+}
+
+/// Hasil terbaik untuk setiap level kampanye.
+/// Bentuk string-nya diawali versi, lalu satu baris "indeks ringkasan"
+/// untuk setiap level yang sudah pernah diselesaikan
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Catatan {
+    pub terbaik: Vec<Option<Ringkasan>>,
+}
+
+const VERSI_CATATAN: &str = "catatan 1";
+
+impl Display for Catatan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", VERSI_CATATAN)?;
+        for (indeks, ringkasan) in self.terbaik.iter().enumerate() {
+            if let Some(ringkasan) = ringkasan {
+                writeln!(f, "{} {}", indeks, ringkasan)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Catatan {
+    type Err = Galat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut baris = s.lines().filter(|baris| !baris.trim().is_empty());
+        if baris.next().map(str::trim) != Some(VERSI_CATATAN) {
+            return Err(Galat::FormatTidakSah("versi catatan tidak dikenal".to_string()));
+        }
+
+        let mut catatan = Catatan::default();
+        for isi in baris {
+            let galat = || Galat::FormatTidakSah(isi.to_string());
+            let (indeks, ringkasan) = isi.trim().split_once(' ').ok_or_else(galat)?;
+            let indeks: usize = indeks.parse().map_err(|_| galat())?;
+            if catatan.terbaik.len() <= indeks {
+                catatan.terbaik.resize(indeks + 1, None);
+            }
+            catatan.terbaik[indeks] = Some(ringkasan.parse()?);
+        }
+        Ok(catatan)
+    }
+}
+
+/// Urutan level yang dimainkan satu per satu beserta catatan hasil terbaiknya
+#[derive(Debug, Clone)]
+pub struct Kampanye {
+    pub level: Vec<(Level, SyaratBuka)>,
+    pub catatan: Catatan,
+}
+
+impl Kampanye {
+    pub fn new(level: Vec<(Level, SyaratBuka)>) -> Kampanye {
+        let catatan = Catatan { terbaik: vec![None; level.len()] };
+        Kampanye { level, catatan }
+    }
+
+    /// Kampanye dari level bawaan, setiap level terbuka setelah level sebelumnya lulus
+    pub fn bawaan() -> Kampanye {
+        let level = LEVEL_BAWAAN
+            .iter()
+            .enumerate()
+            .map(|(indeks, teks)| {
+                let level = teks.parse().expect("level bawaan harus sah");
+                let syarat = if indeks == 0 { SyaratBuka::Bebas } else { SyaratBuka::LulusSebelumnya };
+                (level, syarat)
+            })
+            .collect();
+        Kampanye::new(level)
+    }
+
+    /// Hasil terbaik untuk level pada indeks
+    pub fn terbaik(&self, indeks: usize) -> Option<&Ringkasan> {
+        self.catatan.terbaik.get(indeks).and_then(|ringkasan| ringkasan.as_ref())
+    }
+
+    /// Jumlah bintang terbaik dari semua level
+    pub fn total_bintang(&self) -> u32 {
+        self.catatan.terbaik.iter().flatten().map(|ringkasan| ringkasan.bintang as u32).sum()
+    }
+
+    /// Apakah level pada indeks sudah bisa dimainkan
+    pub fn terbuka(&self, indeks: usize) -> bool {
+        match self.level.get(indeks) {
+            Some((_, SyaratBuka::Bebas)) => true,
+            Some((_, SyaratBuka::LulusSebelumnya)) => {
+                indeks > 0 && self.terbaik(indeks - 1).is_some_and(Ringkasan::lulus)
+            }
+            Some((_, SyaratBuka::TotalBintang(bintang))) => self.total_bintang() >= *bintang,
+            None => false,
+        }
+    }
+
+    /// Level pada indeks jika sudah bisa dimainkan
+    pub fn ambil(&self, indeks: usize) -> Result<&Level, Galat> {
+        let Some((level, _)) = self.level.get(indeks) else {
+            return Err(Galat::LevelTidakAda(indeks));
+        };
+        if !self.terbuka(indeks) {
+            return Err(Galat::LevelTerkunci(indeks));
+        }
+        Ok(level)
+    }
+
+    /// Membuat sesi untuk memainkan level pada indeks
+    pub fn mulai(&self, indeks: usize) -> Result<Sesi, Galat> {
+        Ok(self.ambil(indeks)?.buat_sesi())
+    }
+
+    /// Mencatat hasil permainan level pada indeks.
+    /// Mengembalikan true jika hasil ini menjadi hasil terbaik yang baru
+    pub fn catat(&mut self, indeks: usize, ringkasan: Ringkasan) -> Result<bool, Galat> {
+        let Some(terbaik) = self.catatan.terbaik.get_mut(indeks) else {
+            return Err(Galat::LevelTidakAda(indeks));
+        };
+        match terbaik {
+            Some(lama) if !ringkasan.lebih_baik(lama) => Ok(false),
+            _ => {
+                *terbaik = Some(ringkasan);
+                Ok(true)
+            }
+        }
+    }
+
+    /// Menyimpan catatan kampanye
+    pub fn simpan(&self, penyimpanan: &mut impl Penyimpanan) {
+        penyimpanan.simpan(self.catatan.to_string());
+    }
+
+    /// Memuat catatan kampanye yang pernah disimpan.
+    /// Catatan untuk level yang tidak ada di kampanye ini diabaikan
+    pub fn muat(&mut self, penyimpanan: &impl Penyimpanan) -> Result<(), Galat> {
+        let Some(isi) = penyimpanan.muat() else { return Ok(()) };
+        let mut catatan: Catatan = isi.parse()?;
+        catatan.terbaik.resize(self.level.len(), None);
+        self.catatan = catatan;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ringkasan(skor: u32, bintang: u8) -> Ringkasan {
+        Ringkasan { skor, bintang, sasaran_tercapai: bintang > 0, ..Ringkasan::default() }
+    }
+
+    #[test]
+    fn kampanye_terbuka() {
+        let mut kampanye = Kampanye::bawaan();
+        kampanye.level[2].1 = SyaratBuka::TotalBintang(3);
+        assert!(kampanye.terbuka(0));
+        assert!(!kampanye.terbuka(1));
+        assert!(!kampanye.terbuka(2));
+        assert!(!kampanye.terbuka(3));
+        assert_eq!(kampanye.mulai(1).unwrap_err(), Galat::LevelTerkunci(1));
+        assert_eq!(kampanye.mulai(3).unwrap_err(), Galat::LevelTidakAda(3));
+
+        kampanye.catat(0, ringkasan(500, 0)).unwrap();
+        assert!(!kampanye.terbuka(1));
+        kampanye.catat(0, ringkasan(700, 2)).unwrap();
+        assert!(kampanye.terbuka(1));
+        assert!(kampanye.mulai(1).is_ok());
+        assert!(!kampanye.terbuka(2));
+
+        kampanye.catat(1, ringkasan(1000, 1)).unwrap();
+        assert_eq!(kampanye.total_bintang(), 3);
+        assert!(kampanye.terbuka(2));
+    }
+
+    #[test]
+    fn kampanye_level_tanpa_bintang() {
+        let level = |teks: &str| -> (Level, SyaratBuka) { (teks.parse().unwrap(), SyaratBuka::LulusSebelumnya) };
+        let mut kampanye = Kampanye::new(vec![
+            level("ukuran 6\nlangkah 10\nsasaran skor 100"),
+            level("ukuran 6\nlangkah 10"),
+        ]);
+        kampanye.level[0].1 = SyaratBuka::Bebas;
+
+        let mut sesi = kampanye.mulai(0).unwrap();
+        sesi.skor = 50;
+        kampanye.catat(0, sesi.ringkasan()).unwrap();
+        assert!(!kampanye.terbuka(1));
+
+        sesi.skor = 100;
+        kampanye.catat(0, sesi.ringkasan()).unwrap();
+        assert_eq!(kampanye.terbaik(0).map(|ringkasan| ringkasan.bintang), Some(0));
+        assert!(kampanye.terbuka(1));
+
+        // skor lebih tinggi tanpa lulus tidak menggantikan hasil yang lulus
+        let gagal = Ringkasan { skor: 900, ..Ringkasan::default() };
+        assert_eq!(kampanye.catat(0, gagal), Ok(false));
+        assert!(kampanye.terbuka(1));
+    }
+
+    #[test]
+    fn kampanye_catat() {
+        let mut kampanye = Kampanye::bawaan();
+        assert_eq!(kampanye.catat(0, ringkasan(700, 1)), Ok(true));
+        assert_eq!(kampanye.catat(0, ringkasan(600, 1)), Ok(false));
+        assert_eq!(kampanye.catat(0, ringkasan(650, 2)), Ok(true));
+        assert_eq!(kampanye.terbaik(0), Some(&ringkasan(650, 2)));
+        assert_eq!(kampanye.catat(9, ringkasan(1, 1)), Err(Galat::LevelTidakAda(9)));
+    }
+
+    #[test]
+    fn kampanye_simpan_muat() {
+        let mut penyimpanan = PenyimpananMemori::default();
+        let mut kampanye = Kampanye::bawaan();
+        kampanye.muat(&penyimpanan).unwrap();
+        assert_eq!(kampanye.total_bintang(), 0);
+
+        kampanye.catat(0, ringkasan(700, 2)).unwrap();
+        kampanye.catat(2, ringkasan(1500, 1)).unwrap();
+        kampanye.simpan(&mut penyimpanan);
+
+        let mut lain = Kampanye::bawaan();
+        lain.muat(&penyimpanan).unwrap();
+        assert_eq!(lain.catatan, kampanye.catatan);
+        assert!(lain.terbuka(1));
+
+        penyimpanan.simpan("catatan 0\n".to_string());
+        assert!(lain.muat(&penyimpanan).is_err());
+    }
+
+    #[test]
+    fn catatan_parse() {
+        let catatan = Catatan { terbaik: vec![Some(ringkasan(700, 2)), None, Some(ringkasan(10, 1))] };
+        let teks = catatan.to_string();
+        assert!(teks.starts_with("catatan 1\n0 skor:700 bintang:2"));
+        assert_eq!(teks.parse(), Ok(catatan));
+        assert!("catatan 1\nx skor:1".parse::<Catatan>().is_err());
+        assert!("catatan 1\n0 skor:x".parse::<Catatan>().is_err());
+    }
+}
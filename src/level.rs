@@ -0,0 +1,780 @@
+//! Level ditulis sebagai berkas teks, satu perintah per baris:
+//!
+//! ```text
+//! // komentar diawali dengan //
+//! ukuran 7
+//! warna merah:2 jingga kuning hijau biru
+//! langkah 20
+//! bintang 1000 2500 4000
+//! sasaran skor 3000
+//! sasaran warna merah 30 atau jeli 10
+//! papan
+//! ? ? ? ? ? ? ?
+//! ? R R . R ? ?
+//! ...
+//! ```
+//!
+//! - `ukuran n` — sisi papan, harus ditulis sebelum `papan`
+//! - `warna nama[:bobot] ...` — warna yang boleh muncul beserta bobotnya (bawaan 1), minimal 3 warna
+//! - `langkah n` atau `waktu detik` — batas langkah atau batas waktu, hanya boleh salah satu
+//! - `bintang a b c` — skor minimal untuk 1, 2, dan 3 bintang, tanpa perintah ini level tidak memberi bintang
+//! - `sasaran ...` — satu sasaran per baris (semua harus tercapai), alternatifnya dipisah `atau`.
+//!   Sasaran dasar: `skor n`, `warna nama n`, `jeli n` (paling banyak seluruh lapis jeli di papan), `bahan n`
+//! - `keluar k ...` — kolom (mulai dari 1) yang petak paling bawahnya menjadi jalan keluar bahan
+//! - `bahan n m` — satu bahan muncul dari atas setiap n langkah, paling banyak m bahan di papan
+//! - `benih n` — benih permen acak agar setiap permainan level ini sama, tanpa benih setiap permainan berbeda
+//! - `bom n h m` — satu bom dengan sisa h giliran muncul dari atas setiap n langkah, paling banyak m bom di papan
+//! - `papan` — diikuti tepat `ukuran` baris petak, bawaannya semua petak `?`
+//!
+//! Petak papan: `?` permen acak, `.` lubang, `#` atau `#n` penghalang dengan n pukulan (1-5),
+//! `%` cokelat, `&` bahan, `$` pelangi yang cocok dengan warna apa pun, atau huruf warna (`RJKHBNU`) yang boleh diikuti tanda spesial `-` (garis mendatar),
+//! `|` (garis tegak), atau `*` (bungkus). Huruf warna yang diikuti `!n` adalah bom dengan sisa n giliran.
+//! Permen boleh diikuti `@` untuk mengunci permen itu
+//! di kandang. Petak selain lubang boleh diakhiri `~` atau `~~` untuk
+//! satu atau dua lapis jeli di bawahnya. Emoji yang ditulis oleh `Display` untuk Papan juga diterima.
+//!
+//! Petak yang sama dipakai untuk membaca Papan langsung dari teks, satu baris per baris papan:
+//!
+//! ```
+//! # use crate::permen_remuk::permenremuk::{Jenis, Papan, Petak};
+//! let papan: Papan = "
+//!     R J K
+//!     K R|~ .
+//!     🟩 🟦 #3
+//! ".parse().unwrap();
+//! assert_eq!(papan.get_jenis((1, 1)), Jenis::GarisTegak);
+//! assert_eq!(papan.get_jeli((1, 1)), 1);
+//! assert_eq!(papan.get_petak((2, 2)), Petak::Penghalang(3));
+//! ```
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::permenremuk::*;
+
+/// Level bawaan, berurutan dari yang paling mudah
+pub const LEVEL_BAWAAN: [&str; 3] = [
+    include_str!("../levels/01.txt"),
+    include_str!("../levels/02.txt"),
+    include_str!("../levels/03.txt"),
+];
+
+/// Kesalahan saat membaca teks, lengkap dengan baris dan kolomnya (mulai dari 1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GalatUrai {
+    pub baris: usize,
+    pub kolom: usize,
+    pub pesan: String,
+}
+
+impl GalatUrai {
+    pub fn new(baris: usize, kolom: usize, pesan: impl Into<String>) -> GalatUrai {
+        GalatUrai { baris, kolom, pesan: pesan.into() }
+    }
+}
+
+impl Display for GalatUrai {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "baris {}, kolom {}: {}", self.baris, self.kolom, self.pesan)
+    }
+}
+
+impl std::error::Error for GalatUrai {}
+
+/// Isi awal satu petak pada tata letak level
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tanda {
+    Acak,
+    Permen(Warna, Jenis),
+    /// Bom dengan sisa giliran sebelum meledak
+    Bom(Warna, u8),
+    Petak(Petak),
+}
+
+impl FromStr for Tanda {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut huruf = s.chars();
+        let tanda = match huruf.next() {
+            Some('?') if s.len() == 1 => Tanda::Acak,
+            Some('.' | '⬛') if huruf.as_str().is_empty() => Tanda::Petak(Petak::Kosong),
+            Some('%' | '🍫') if huruf.as_str().is_empty() => Tanda::Petak(Petak::Cokelat),
+            Some('&' | '🍒') if huruf.as_str().is_empty() => Tanda::Permen(Warna::Merah, Jenis::Bahan),
+            Some('$' | '🌈') if huruf.as_str().is_empty() => Tanda::Permen(Warna::Merah, Jenis::Pelangi),
+            Some('#' | '⬜') => {
+                let nyawa = match huruf.as_str() {
+                    "" => 1,
+                    angka => angka.parse().map_err(|_| format!("penghalang tidak sah `{}`", s))?,
+                };
+                if !(1..=PENGHALANG_MAKS).contains(&nyawa) {
+                    return Err(format!("penghalang harus 1 sampai {} pukulan, bukan {}", PENGHALANG_MAKS, nyawa));
+                }
+                Tanda::Petak(Petak::Penghalang(nyawa))
+            }
+            Some(awal) => {
+                let warna = awal
+                    .to_string()
+                    .parse()
+                    .map_err(|_| format!("petak tidak dikenal `{}`", s))?;
+                if let Some(sisa) = huruf.as_str().strip_prefix('!') {
+                    let sisa: u8 = sisa.parse().map_err(|_| format!("bom tidak sah `{}`", s))?;
+                    if sisa == 0 {
+                        return Err(format!("sisa giliran bom tidak boleh 0 `{}`", s));
+                    }
+                    return Ok(Tanda::Bom(warna, sisa));
+                }
+                let jenis = match huruf.as_str() {
+                    "" => Jenis::Biasa,
+                    "-" => Jenis::GarisMendatar,
+                    "|" => Jenis::GarisTegak,
+                    "*" => Jenis::Bungkus,
+                    _ => return Err(format!("tanda spesial tidak dikenal `{}`", s)),
+                };
+                Tanda::Permen(warna, jenis)
+            }
+            None => return Err("petak kosong".to_string()),
+        };
+        Ok(tanda)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Level {
+    pub ukuran: usize,
+    pub palet: Vec<(Warna, u32)>,
+    pub tata_letak: Vec<Vec<Tanda>>,
+    /// Lapisan jeli awal setiap petak
+    pub jeli: Vec<Vec<u8>>,
+    /// Petak yang permennya terkunci kandang sejak awal
+    pub kunci: Vec<Vec<bool>>,
+    /// Kolom yang petak paling bawahnya menjadi jalan keluar bahan
+    pub keluar: Vec<usize>,
+    pub jadwal_bahan: Option<JadwalBahan>,
+    pub jadwal_bom: Option<JadwalBom>,
+    pub batas_langkah: Option<u32>,
+    pub waktu_ms: Option<u64>,
+    pub sasaran: Option<Sasaran>,
+    pub ambang_bintang: Option<[u32; 3]>,
+    /// Benih untuk permen acak, tanpa benih setiap permainan level ini berbeda
+    pub benih: Option<u64>,
+}
+
+// Memecah baris menjadi kata beserta kolomnya (mulai dari 1)
+fn kata_kata(baris: &str) -> Vec<(usize, &str)> {
+    let mut hasil = vec![];
+    let mut awal = None;
+    for (kolom, (idx, huruf)) in baris.char_indices().enumerate() {
+        match (huruf.is_whitespace(), awal) {
+            (false, None) => awal = Some((kolom + 1, idx)),
+            (true, Some((kol, mulai))) => {
+                hasil.push((kol, &baris[mulai..idx]));
+                awal = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((kol, mulai)) = awal {
+        hasil.push((kol, &baris[mulai..]));
+    }
+    hasil
+}
+
+// Membaca satu petak papan beserta lapisan jeli yang ditulis dengan `~` di akhir
+// dan kandang yang ditulis dengan `@` sebelum jeli
+fn petak_papan(kata: &str) -> Result<(Tanda, u8, bool), String> {
+    let tanda = kata.trim_end_matches('~');
+    let jeli = (kata.len() - tanda.len()) as u8;
+    if jeli > JELI_MAKS {
+        return Err(format!("jeli paling banyak {} lapis, bukan {}", JELI_MAKS, jeli));
+    }
+    let (tanda, kunci) = match tanda.strip_suffix('@') {
+        Some(tanda) => (tanda, true),
+        None => (tanda, false),
+    };
+    let tanda: Tanda = tanda.parse()?;
+    if jeli > 0 && tanda == Tanda::Petak(Petak::Kosong) {
+        return Err(format!("lubang tidak bisa diberi jeli `{}`", kata));
+    }
+    if kunci && matches!(tanda, Tanda::Petak(_)) {
+        return Err(format!("hanya permen yang bisa dikunci `{}`", kata));
+    }
+    Ok((tanda, jeli, kunci))
+}
+
+// Membaca satu baris papan yang harus berisi tepat ukuran petak
+fn baris_papan(baris: usize, isi: &str, ukuran: usize) -> Result<Vec<(Tanda, u8, bool)>, GalatUrai> {
+    let kata = kata_kata(isi);
+    if kata.len() != ukuran {
+        return Err(GalatUrai::new(baris, 1, format!("baris papan harus punya {} petak", ukuran)));
+    }
+    kata.into_iter()
+        .map(|(kol, kata)| petak_papan(kata).map_err(|pesan| GalatUrai::new(baris, kol, pesan)))
+        .collect()
+}
+
+type Kisi<T> = Vec<Vec<T>>;
+
+// Memisahkan tata letak dari lapisan jeli dan kandang
+fn pisahkan_lapisan(petak: Kisi<(Tanda, u8, bool)>) -> (Kisi<Tanda>, Kisi<u8>, Kisi<bool>) {
+    let mut hasil = (vec![], vec![], vec![]);
+    for baris in petak {
+        let (tanda, lapisan): (Vec<_>, Vec<_>) = baris.into_iter().map(|(t, j, k)| (t, (j, k))).unzip();
+        let (jeli, kunci) = lapisan.into_iter().unzip();
+        hasil.0.push(tanda);
+        hasil.1.push(jeli);
+        hasil.2.push(kunci);
+    }
+    hasil
+}
+
+fn angka<T: FromStr>(baris: usize, (kolom, kata): (usize, &str)) -> Result<T, GalatUrai> {
+    kata.parse().map_err(|_| GalatUrai::new(baris, kolom, format!("`{}` bukan angka yang sah", kata)))
+}
+
+fn warna(baris: usize, (kolom, kata): (usize, &str)) -> Result<Warna, GalatUrai> {
+    kata.parse().map_err(|_| GalatUrai::new(baris, kolom, format!("warna tidak dikenal `{}`", kata)))
+}
+
+// Membaca satu sasaran dasar dari kata-kata setelah `sasaran` atau `atau`
+fn sasaran_dasar(baris: usize, kolom: usize, kata: &[(usize, &str)]) -> Result<Sasaran, GalatUrai> {
+    let jumlah_salah = || GalatUrai::new(baris, kolom, "jumlah isian sasaran tidak sesuai");
+    let sasaran = match kata {
+        [(_, "skor"), n] => Sasaran::Skor(angka(baris, *n)?),
+        [(_, "warna"), w, n] => Sasaran::RemukanWarna(warna(baris, *w)?, angka(baris, *n)?),
+        [(_, "jeli"), n] => Sasaran::BersihkanJeli(angka(baris, *n)?),
+        [(_, "bahan"), n] => Sasaran::TurunkanBahan(angka(baris, *n)?),
+        [(_, "skor" | "warna" | "jeli" | "bahan"), ..] | [] => return Err(jumlah_salah()),
+        [(kol, lain), ..] => {
+            return Err(GalatUrai::new(baris, *kol, format!("sasaran tidak dikenal `{}`", lain)))
+        }
+    };
+    Ok(sasaran)
+}
+
+impl FromStr for Level {
+    type Err = GalatUrai;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ukuran = None;
+        let mut palet = SEMUA_WARNA.iter().map(|warna| (warna.clone(), 1)).collect();
+        let mut tata_letak = None;
+        let mut batas_langkah = None;
+        let mut waktu_ms = None;
+        let mut semua_sasaran = vec![];
+        let mut ambang_bintang = None;
+        let mut keluar = vec![];
+        let mut jadwal_bahan = None;
+        let mut jadwal_bom = None;
+        let mut benih = None;
+        // letak perintah `bahan` atau petak `&` pertama, untuk galat kolom keluar
+        let mut letak_bahan = None;
+        // letak dan target setiap sasaran jeli, diperiksa setelah papan terbaca
+        let mut sasaran_jeli = vec![];
+        let satu_batas = |baris, kolom| GalatUrai::new(baris, kolom, "level hanya boleh punya batas langkah atau batas waktu");
+
+        let mut semua_baris = s.lines().enumerate().map(|(idx, isi)| (idx + 1, isi));
+        while let Some((baris, isi)) = semua_baris.next() {
+            let isi = isi.split("//").next().unwrap_or_default();
+            let kata = kata_kata(isi);
+            let Some(((kolom, perintah), sisa)) = kata.split_first() else { continue };
+            let (kolom, perintah) = (*kolom, *perintah);
+
+            match (perintah, sisa) {
+                ("ukuran", [n]) => {
+                    let n: usize = angka(baris, *n)?;
+                    if !(PAPAN_MIN..=PAPAN_MAX).contains(&n) {
+                        return Err(GalatUrai::new(
+                            baris, sisa[0].0,
+                            format!("ukuran harus {} sampai {}", PAPAN_MIN, PAPAN_MAX),
+                        ));
+                    }
+                    ukuran = Some(n);
+                }
+                ("warna", [..]) => {
+                    let mut baru: Vec<(Warna, u32)> = vec![];
+                    for (kol, kata) in sisa {
+                        let (nama, bobot) = match kata.split_once(':') {
+                            Some((nama, bobot)) => (nama, angka(baris, (*kol, bobot))?),
+                            None => (*kata, 1),
+                        };
+                        let w = warna(baris, (*kol, nama))?;
+                        if bobot == 0 {
+                            return Err(GalatUrai::new(baris, *kol, "bobot warna tidak boleh 0"));
+                        }
+                        if baru.iter().any(|(lain, _)| *lain == w) {
+                            return Err(GalatUrai::new(baris, *kol, format!("warna `{}` ditulis dua kali", nama)));
+                        }
+                        baru.push((w, bobot));
+                    }
+                    if baru.len() < 3 {
+                        return Err(GalatUrai::new(baris, kolom, "minimal harus ada 3 warna"));
+                    }
+                    palet = baru;
+                }
+                ("langkah", [n]) => {
+                    if waktu_ms.is_some() { return Err(satu_batas(baris, kolom)) }
+                    batas_langkah = Some(angka(baris, *n)?);
+                }
+                ("benih", [n]) => benih = Some(angka(baris, *n)?),
+                ("waktu", [n]) => {
+                    if batas_langkah.is_some() { return Err(satu_batas(baris, kolom)) }
+                    waktu_ms = Some(angka::<u64>(baris, *n)? * 1_000);
+                }
+                ("bintang", [a, b, c]) => {
+                    let ambang: [u32; 3] = [angka(baris, *a)?, angka(baris, *b)?, angka(baris, *c)?];
+                    if ambang[0] > ambang[1] || ambang[1] > ambang[2] {
+                        return Err(GalatUrai::new(baris, a.0, "ambang bintang harus berurutan naik"));
+                    }
+                    if ambang[2] == 0 {
+                        return Err(GalatUrai::new(baris, c.0, "ambang 3 bintang harus lebih dari 0"));
+                    }
+                    ambang_bintang = Some(ambang);
+                }
+                ("sasaran", [..]) => {
+                    let mut pilihan = vec![];
+                    for bagian in sisa.split(|(_, kata)| *kata == "atau") {
+                        let sasaran = sasaran_dasar(baris, kolom, bagian)?;
+                        if let Sasaran::BersihkanJeli(n) = sasaran {
+                            sasaran_jeli.push((baris, bagian[0].0, n));
+                        }
+                        pilihan.push(sasaran);
+                    }
+                    semua_sasaran.push(match pilihan.len() {
+                        1 => pilihan.remove(0),
+                        _ => Sasaran::Atau(pilihan),
+                    });
+                }
+                ("keluar", [_, ..]) => {
+                    let Some(ukuran) = ukuran else {
+                        return Err(GalatUrai::new(baris, kolom, "ukuran harus ditulis sebelum keluar"));
+                    };
+                    for kata in sisa {
+                        let k: usize = angka(baris, *kata)?;
+                        if !(1..=ukuran).contains(&k) {
+                            return Err(GalatUrai::new(baris, kata.0, format!("kolom keluar harus 1 sampai {}", ukuran)));
+                        }
+                        if !keluar.contains(&(k - 1)) {
+                            keluar.push(k - 1);
+                        }
+                    }
+                }
+                ("bahan", [n, m]) => {
+                    let jadwal = JadwalBahan { setiap: angka(baris, *n)?, maks: angka(baris, *m)? };
+                    if jadwal.setiap == 0 || jadwal.maks == 0 {
+                        return Err(GalatUrai::new(baris, n.0, "jadwal bahan tidak boleh 0"));
+                    }
+                    jadwal_bahan = Some(jadwal);
+                    letak_bahan = letak_bahan.or(Some((baris, kolom)));
+                }
+                ("bom", [n, h, m]) => {
+                    let jadwal = JadwalBom { setiap: angka(baris, *n)?, hitungan: angka(baris, *h)?, maks: angka(baris, *m)? };
+                    if jadwal.setiap == 0 || jadwal.hitungan == 0 || jadwal.maks == 0 {
+                        return Err(GalatUrai::new(baris, n.0, "jadwal bom tidak boleh 0"));
+                    }
+                    jadwal_bom = Some(jadwal);
+                }
+                ("papan", []) => {
+                    let Some(ukuran) = ukuran else {
+                        return Err(GalatUrai::new(baris, kolom, "ukuran harus ditulis sebelum papan"));
+                    };
+                    let mut petak = vec![];
+                    for _ in 0..ukuran {
+                        let Some((baris, isi)) = semua_baris.next() else {
+                            return Err(GalatUrai::new(baris, kolom, format!("papan harus punya {} baris", ukuran)));
+                        };
+                        let isian = baris_papan(baris, isi, ukuran)?;
+                        if letak_bahan.is_none() {
+                            letak_bahan = kata_kata(isi)
+                                .into_iter()
+                                .zip(&isian)
+                                .find(|(_, (tanda, _, _))| matches!(tanda, Tanda::Permen(_, Jenis::Bahan)))
+                                .map(|((kol, _), _)| (baris, kol));
+                        }
+                        petak.push(isian);
+                    }
+                    tata_letak = Some((baris, petak));
+                }
+                ("ukuran" | "langkah" | "waktu" | "bintang" | "keluar" | "bahan" | "bom" | "benih" | "papan", _) => {
+                    return Err(GalatUrai::new(baris, kolom, format!("jumlah isian `{}` tidak sesuai", perintah)));
+                }
+                _ => return Err(GalatUrai::new(baris, kolom, format!("perintah tidak dikenal `{}`", perintah))),
+            }
+        }
+
+        let Some(ukuran) = ukuran else {
+            return Err(GalatUrai::new(1, 1, "ukuran papan belum ditulis"));
+        };
+        let (baris_papan, (tata_letak, jeli, kunci)) = match tata_letak {
+            Some((baris, petak)) if petak.len() == ukuran => (baris, pisahkan_lapisan(petak)),
+            Some((baris, _)) => return Err(GalatUrai::new(baris, 1, "ukuran ditulis ulang setelah papan")),
+            None => (
+                0,
+                (vec![vec![Tanda::Acak; ukuran]; ukuran], vec![vec![0; ukuran]; ukuran], vec![vec![false; ukuran]; ukuran]),
+            ),
+        };
+        let sasaran = match semua_sasaran.len() {
+            0 => None,
+            1 => semua_sasaran.pop(),
+            _ => Some(Sasaran::Dan(semua_sasaran)),
+        };
+        if let Some((baris, kolom)) = letak_bahan.filter(|_| keluar.is_empty()) {
+            return Err(GalatUrai::new(baris, kolom, "level dengan bahan harus punya kolom keluar"));
+        }
+        let total_jeli: u32 = jeli.iter().flatten().map(|lapisan| *lapisan as u32).sum();
+        if let Some((baris, kolom, n)) = sasaran_jeli.into_iter().find(|(_, _, n)| *n > total_jeli) {
+            return Err(GalatUrai::new(
+                baris, kolom,
+                format!("sasaran jeli {} melebihi {} lapis jeli di papan", n, total_jeli),
+            ));
+        }
+
+        let level = Level {
+            ukuran,
+            palet,
+            tata_letak,
+            jeli,
+            kunci,
+            keluar,
+            jadwal_bahan,
+            jadwal_bom,
+            batas_langkah,
+            waktu_ms,
+            sasaran,
+            ambang_bintang,
+            benih,
+        };
+        if !level.papan_awal().semua_dempet().is_empty() {
+            return Err(GalatUrai::new(baris_papan, 1, "papan awal punya permen dempet"));
+        }
+        Ok(level)
+    }
+}
+
+impl FromStr for Papan {
+    type Err = GalatUrai;
+
+    /// Membaca papan yang ditulis dengan petak tata letak level atau emoji,
+    /// baris kosong diabaikan. Petak `?` diisi permen acak tanpa membuat dempet,
+    /// selalu dengan benih 0 sehingga teks yang sama menghasilkan papan yang sama
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let semua_baris: Vec<(usize, &str)> = s
+            .lines()
+            .enumerate()
+            .map(|(idx, isi)| (idx + 1, isi))
+            .filter(|(_, isi)| !isi.trim().is_empty())
+            .collect();
+        let ukuran = semua_baris.len();
+        if !(PAPAN_MIN..=PAPAN_MAX).contains(&ukuran) {
+            let baris = semua_baris.first().map_or(1, |(baris, _)| *baris);
+            return Err(GalatUrai::new(baris, 1, format!("papan harus punya {} sampai {} baris", PAPAN_MIN, PAPAN_MAX)));
+        }
+        let petak = semua_baris
+            .into_iter()
+            .map(|(baris, isi)| baris_papan(baris, isi, ukuran))
+            .collect::<Result<_, _>>()?;
+        let (tata_letak, jeli, kunci) = pisahkan_lapisan(petak);
+
+        let level = Level {
+            ukuran,
+            palet: SEMUA_WARNA.iter().map(|warna| (warna.clone(), 1)).collect(),
+            tata_letak,
+            jeli,
+            kunci,
+            keluar: vec![],
+            jadwal_bahan: None,
+            jadwal_bom: None,
+            batas_langkah: None,
+            waktu_ms: None,
+            sasaran: None,
+            ambang_bintang: None,
+            benih: Some(0),
+        };
+        Ok(level.buat_papan())
+    }
+}
+
+impl Level {
+    // Papan berisi permen yang ditulis di tata letak, petak acak belum diisi
+    fn papan_awal(&self) -> Papan {
+        let benih = self.benih.unwrap_or_else(rand::random);
+        let mut papan = Papan::dengan_benih(self.ukuran, self.palet.len(), benih);
+        papan.palet = self.palet.clone();
+        papan.keluar = self.keluar.clone();
+        for (y, baris) in self.tata_letak.iter().enumerate() {
+            for (x, tanda) in baris.iter().enumerate() {
+                match tanda {
+                    Tanda::Acak => papan.set_petak((y, x), Petak::Kosong),
+                    Tanda::Permen(warna, jenis) => {
+                        papan.set_warna((y, x), warna.clone());
+                        papan.set_jenis((y, x), jenis.clone());
+                    }
+                    Tanda::Bom(warna, sisa) => {
+                        papan.set_warna((y, x), warna.clone());
+                        papan.set_bom((y, x), *sisa);
+                    }
+                    Tanda::Petak(petak) => papan.set_petak((y, x), petak.clone()),
+                }
+                papan.set_jeli((y, x), self.jeli[y][x]);
+                papan.set_kunci((y, x), self.kunci[y][x]);
+            }
+        }
+        papan
+    }
+
+    /// Membuat papan sesuai level ini, petak acak diisi tanpa permen dempet
+    pub fn buat_papan(&self) -> Papan {
+        let papan = self.papan_awal();
+        let mut acak = vec![];
+        for (y, baris) in self.tata_letak.iter().enumerate() {
+            for (x, tanda) in baris.iter().enumerate() {
+                if *tanda == Tanda::Acak {
+                    papan.set_petak((y, x), Petak::Biasa);
+                    acak.push((y, x));
+                }
+            }
+        }
+        papan.acak_isian(&acak);
+        papan
+    }
+
+    /// Membuat sesi baru untuk memainkan level ini
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::level::{Level, LEVEL_BAWAAN};
+    /// let level: Level = LEVEL_BAWAAN[0].parse().unwrap();
+    /// let sesi = level.buat_sesi();
+    /// assert_eq!(sesi.papan.ukuran, level.ukuran);
+    /// ```
+    pub fn buat_sesi(&self) -> Sesi {
+        let mut sesi = Sesi::new(self.buat_papan());
+        sesi.ambang_bintang = self.ambang_bintang;
+        sesi.batas_langkah = self.batas_langkah;
+        sesi.sisa_waktu_ms = self.waktu_ms;
+        sesi.sasaran = self.sasaran.clone();
+        sesi.jadwal_bahan = self.jadwal_bahan;
+        sesi.jadwal_bom = self.jadwal_bom;
+        sesi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_bawaan() {
+        for teks in LEVEL_BAWAAN {
+            let level: Level = teks.parse().unwrap();
+            let sesi = level.buat_sesi();
+            assert_eq!(sesi.papan.ukuran, level.ukuran);
+            assert!(sesi.papan.semua_dempet().is_empty());
+            assert!(sesi.sasaran.is_some());
+        }
+    }
+
+    #[test]
+    fn level_urai() {
+        let teks = "
+            // level percobaan
+            ukuran 5
+            warna merah:3 jingga kuning:2
+            langkah 15
+            bintang 100 200 300
+            sasaran skor 500
+            sasaran warna merah 20 atau jeli 4
+            papan
+            ? ? ? ? ?
+            ? R- . #3 ?
+            ?~ ?~ ?~~ ? ?
+            J ? ? ? K*
+            ? ? ? ? ?
+        ";
+        let level: Level = teks.parse().unwrap();
+        assert_eq!(level.ukuran, 5);
+        assert_eq!(level.palet, vec![(Warna::Merah, 3), (Warna::Jingga, 1), (Warna::Kuning, 2)]);
+        assert_eq!(level.batas_langkah, Some(15));
+        assert_eq!(level.waktu_ms, None);
+        assert_eq!(level.ambang_bintang, Some([100, 200, 300]));
+        assert_eq!(level.sasaran, Some(Sasaran::Dan(vec![
+            Sasaran::Skor(500),
+            Sasaran::Atau(vec![Sasaran::RemukanWarna(Warna::Merah, 20), Sasaran::BersihkanJeli(4)]),
+        ])));
+        assert_eq!(level.tata_letak[1][1], Tanda::Permen(Warna::Merah, Jenis::GarisMendatar));
+        assert_eq!(level.tata_letak[1][2], Tanda::Petak(Petak::Kosong));
+        assert_eq!(level.tata_letak[1][3], Tanda::Petak(Petak::Penghalang(3)));
+        assert_eq!(level.tata_letak[3][4], Tanda::Permen(Warna::Kuning, Jenis::Bungkus));
+        assert_eq!(level.jeli[2], vec![1, 1, 2, 0, 0]);
+
+        let sesi = level.buat_sesi();
+        assert_eq!(sesi.batas_langkah, Some(15));
+        assert_eq!(sesi.papan.get_jenis((1, 1)), Jenis::GarisMendatar);
+        assert_eq!(sesi.papan.get_petak((1, 3)), Petak::Penghalang(3));
+        assert_eq!(sesi.papan.get_warna((3, 0)), Warna::Jingga);
+        assert_eq!(sesi.papan.sisa_jeli(), 4);
+        for y in 0..5 {
+            for x in 0..5 {
+                if sesi.papan.punya_permen((y, x)) {
+                    let warna = sesi.papan.get_warna((y, x));
+                    assert!(sesi.papan.palet.iter().any(|(w, _)| *w == warna));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn level_waktu() {
+        let level: Level = "ukuran 6\nwaktu 90".parse().unwrap();
+        assert_eq!(level.waktu_ms, Some(90_000));
+        assert_eq!(level.buat_sesi().sisa_waktu_ms, Some(90_000));
+        assert_eq!(level.sasaran, None);
+    }
+
+    #[test]
+    fn level_benih() {
+        let level: Level = "ukuran 6\nbenih 42".parse().unwrap();
+        assert_eq!(level.benih, Some(42));
+        let sesi = level.buat_sesi();
+        sesi.papan.remukan((5, 0));
+        let lain = level.buat_sesi();
+        lain.papan.remukan((5, 0));
+        assert_eq!(sesi.papan.to_string(), lain.papan.to_string());
+
+        let teks = "? ? ?\n? ? ?\n? ? ?";
+        assert_eq!(teks.parse::<Papan>().unwrap().to_string(), teks.parse::<Papan>().unwrap().to_string());
+    }
+
+    #[test]
+    fn papan_urai() {
+        let papan: Papan = "
+            R J $ H
+            K R*@ . B
+            H #3 R| J
+            ? ? ? %
+        ".parse().unwrap();
+        assert_eq!(papan.ukuran, 4);
+        assert_eq!(papan.get_warna((0, 3)), Warna::Hijau);
+        assert_eq!(papan.get_jenis((0, 2)), Jenis::Pelangi);
+        assert_eq!(papan.get_jenis((1, 1)), Jenis::Bungkus);
+        assert!(papan.terkunci((1, 1)));
+        assert_eq!(papan.get_petak((1, 2)), Petak::Kosong);
+        assert_eq!(papan.get_petak((2, 1)), Petak::Penghalang(3));
+        assert_eq!(papan.get_jenis((2, 2)), Jenis::GarisTegak);
+        assert!((0..3).all(|x| papan.punya_permen((3, x))));
+        assert_eq!(papan.get_petak((3, 3)), Petak::Cokelat);
+        assert!(papan.semua_dempet().is_empty());
+
+        // bentuk huruf menulis ulang semua isi papan
+        let teks = format!("{:#}", papan);
+        let lain: Papan = teks.parse().unwrap();
+        assert_eq!(format!("{:#}", lain), teks);
+
+        // bentuk emoji ditulis ulang persis
+        let emoji = papan.to_string();
+        assert!(emoji.starts_with("🟥 🟧 🌈 🟩 \n🟨 🟥@ ⬛ 🟦 \n🟩 ⬜ 🟥 🟧"));
+        assert_eq!(emoji.parse::<Papan>().unwrap().to_string(), emoji);
+    }
+
+    #[test]
+    fn papan_urai_galat() {
+        let galat = |teks: &str| teks.parse::<Papan>().unwrap_err();
+        assert_eq!(galat("R J\nK R"), GalatUrai::new(1, 1, "papan harus punya 3 sampai 10 baris"));
+        assert_eq!(galat("R J K\n\nK R\nJ K R"), GalatUrai::new(3, 1, "baris papan harus punya 3 petak"));
+        assert_eq!(galat("R J K\nK 🟫 Q\nJ K R"), GalatUrai::new(2, 5, "petak tidak dikenal `Q`"));
+        assert_eq!(galat("R J K\nK R+ J\nJ K R"), GalatUrai::new(2, 3, "tanda spesial tidak dikenal `R+`"));
+        assert_eq!(galat("R J K\nK R~~~ J\nJ K R"), GalatUrai::new(2, 3, "jeli paling banyak 2 lapis, bukan 3"));
+        assert_eq!(galat("R J K\nK .~ J\nJ K R"), GalatUrai::new(2, 3, "lubang tidak bisa diberi jeli `.~`"));
+        assert_eq!(galat("R J K\nK #@ J\nJ K R"), GalatUrai::new(2, 3, "hanya permen yang bisa dikunci `#@`"));
+        assert_eq!(galat("R J K\nK && J\nJ K R"), GalatUrai::new(2, 3, "petak tidak dikenal `&&`"));
+        assert_eq!(galat("R J K\nK R!0 J\nJ K R"), GalatUrai::new(2, 3, "sisa giliran bom tidak boleh 0 `R!0`"));
+        assert_eq!(galat("R J K\nK R!x J\nJ K R"), GalatUrai::new(2, 3, "bom tidak sah `R!x`"));
+    }
+
+    #[test]
+    fn level_galat() {
+        let galat = |teks: &str| teks.parse::<Level>().unwrap_err();
+
+        assert_eq!(galat("ukuran 5\nlompat 3"), GalatUrai::new(2, 1, "perintah tidak dikenal `lompat`"));
+        assert_eq!(galat("ukuran 50"), GalatUrai::new(1, 8, "ukuran harus 3 sampai 10"));
+        assert_eq!(galat("ukuran 5\nwarna merah biru"), GalatUrai::new(2, 1, "minimal harus ada 3 warna"));
+        assert_eq!(galat("ukuran 5\nwarna merah biru:x hijau").baris, 2);
+        assert_eq!(galat("ukuran 5\nwarna merah abu hijau"), GalatUrai::new(2, 13, "warna tidak dikenal `abu`"));
+        assert_eq!(galat("ukuran 5\nbintang 300 200 100").pesan, "ambang bintang harus berurutan naik");
+        assert_eq!(galat("ukuran 5\nbintang 0 0 0"), GalatUrai::new(2, 13, "ambang 3 bintang harus lebih dari 0"));
+        assert_eq!(galat("ukuran 5\nsasaran skor").pesan, "jumlah isian sasaran tidak sesuai");
+        assert_eq!(
+            galat("ukuran 5\nlangkah 5\n  waktu 30"),
+            GalatUrai::new(3, 3, "level hanya boleh punya batas langkah atau batas waktu")
+        );
+        assert_eq!(
+            galat("ukuran 5\nwaktu 30\nlangkah 5"),
+            GalatUrai::new(3, 1, "level hanya boleh punya batas langkah atau batas waktu")
+        );
+        assert_eq!(galat("langkah 5"), GalatUrai::new(1, 1, "ukuran papan belum ditulis"));
+        assert_eq!(galat("papan\n? ? ?").pesan, "ukuran harus ditulis sebelum papan");
+        assert_eq!(galat("ukuran 3\npapan\n? ? ?\n? ?"), GalatUrai::new(4, 1, "baris papan harus punya 3 petak"));
+        assert_eq!(galat("ukuran 3\npapan\n? ? ?"), GalatUrai::new(2, 1, "papan harus punya 3 baris"));
+        assert_eq!(galat("ukuran 3\npapan\n? ? ?\n? #9 ?\n? ? ?"), GalatUrai::new(4, 3, "penghalang harus 1 sampai 5 pukulan, bukan 9"));
+        assert_eq!(galat("ukuran 3\npapan\n? ? ?\n? Q ?\n? ? ?"), GalatUrai::new(4, 3, "petak tidak dikenal `Q`"));
+        assert_eq!(galat("ukuran 3\npapan\nR R R\n? ? ?\n? ? ?"), GalatUrai::new(2, 1, "papan awal punya permen dempet"));
+        assert_eq!(galat("ukuran 3\nkeluar 2 4"), GalatUrai::new(2, 10, "kolom keluar harus 1 sampai 3"));
+        assert_eq!(galat("ukuran 3\nbahan 0 2").pesan, "jadwal bahan tidak boleh 0");
+        assert_eq!(galat("ukuran 3\nbom 3 0 1").pesan, "jadwal bom tidak boleh 0");
+        assert_eq!(galat("ukuran 3\nbom 3 1").pesan, "jumlah isian `bom` tidak sesuai");
+        assert_eq!(
+            galat("ukuran 3\nsasaran skor 100 atau jeli 3\npapan\n? ?~ ?\n? ?~ ?\n? ? ?"),
+            GalatUrai::new(2, 23, "sasaran jeli 3 melebihi 2 lapis jeli di papan")
+        );
+        assert_eq!(galat("ukuran 3\nsasaran jeli 1"), GalatUrai::new(2, 9, "sasaran jeli 1 melebihi 0 lapis jeli di papan"));
+        assert_eq!(galat("ukuran 3\nbahan 3 1"), GalatUrai::new(2, 1, "level dengan bahan harus punya kolom keluar"));
+        assert_eq!(
+            galat("ukuran 3\npapan\n? ? ?\n? ? &\n? ? ?"),
+            GalatUrai::new(4, 5, "level dengan bahan harus punya kolom keluar")
+        );
+    }
+
+    #[test]
+    fn level_bahan() {
+        let teks = "
+            ukuran 5
+            keluar 2 5 2
+            bahan 4 2
+            sasaran bahan 3
+            papan
+            ? & ? ? ?
+            ? ? ? ? ?
+            ? ? ? ? ?
+            ? ? ? ? ?
+            ? ? ? ? ?
+        ";
+        let level: Level = teks.parse().unwrap();
+        assert_eq!(level.keluar, vec![1, 4]);
+        assert_eq!(level.tata_letak[0][1], Tanda::Permen(Warna::Merah, Jenis::Bahan));
+
+        let sesi = level.buat_sesi();
+        assert_eq!(sesi.jadwal_bahan, Some(JadwalBahan { setiap: 4, maks: 2 }));
+        assert_eq!(sesi.papan.keluar, vec![1, 4]);
+        assert!(sesi.papan.punya_bahan((0, 1)));
+    }
+
+    #[test]
+    fn level_bom() {
+        let teks = "
+            ukuran 4
+            bom 3 8 2
+            papan
+            ? ? ? ?
+            ? B!5 ? ?
+            ? ? ? ?
+            ? ? ? ?
+        ";
+        let level: Level = teks.parse().unwrap();
+        assert_eq!(level.tata_letak[1][1], Tanda::Bom(Warna::Biru, 5));
+
+        let sesi = level.buat_sesi();
+        assert_eq!(sesi.jadwal_bom, Some(JadwalBom { setiap: 3, hitungan: 8, maks: 2 }));
+        assert_eq!((sesi.papan.get_warna((1, 1)), sesi.papan.get_bom((1, 1))), (Warna::Biru, 5));
+        assert_eq!(sesi.papan.jumlah_bom(), 1);
+    }
+}
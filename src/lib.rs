@@ -1,24 +1,104 @@
 // #![allow(dead_code)]
+pub mod kampanye;
+pub mod level;
+pub mod masukan;
+pub mod permainan;
 pub mod permenremuk;
+pub mod simpanan;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use wasm_bindgen::prelude::*;
+use kampanye::{Kampanye, Penyimpanan, PenyimpananMemori};
+use permainan::{galat_js, Permainan};
 use permenremuk::*;
 
+// Jumlah giliran yang bisa diurungkan saat bermain bebas atau menguji level
+const BATAS_RIWAYAT: usize = 50;
+
+// Permainan bawaan untuk fungsi-fungsi bebas di bawah. JS yang butuh beberapa
+// papan sekaligus sebaiknya membuat Permainan sendiri
 thread_local! {
-    static PAPAN: Papan = Papan::new(7, 3);
+    static PERMAINAN: RefCell<Permainan> = RefCell::new(Permainan::dari_pilihan(Pilihan {
+        batas_riwayat: BATAS_RIWAYAT,
+        ..Pilihan::default()
+    }));
     static DEMPET: RefCell<Dempet> = RefCell::new(Dempet::new());
+    static KAMPANYE: RefCell<Kampanye> = RefCell::new(Kampanye::bawaan());
+    static LEVEL_AKTIF: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn dengan_papan<T>(aksi: impl FnOnce(&Papan) -> T) -> T {
+    PERMAINAN.with(|permainan| aksi(&permainan.borrow().sesi.papan))
+}
+
+// Mengganti permainan bawaan, pendengar peristiwa tetap terpasang
+fn ganti_permainan(mut baru: Permainan) {
+    PERMAINAN.with(|permainan| {
+        let mut permainan = permainan.borrow_mut();
+        baru.pendengar = permainan.pendengar.take();
+        *permainan = baru;
+    });
+    DEMPET.with(|dmpt| { dmpt.borrow_mut().kosongkan(); });
+    LEVEL_AKTIF.with(|aktif| aktif.set(None));
 }
 
 #[wasm_bindgen(js_name = buatPapan)]
 pub fn buat_papan() -> String {
-    PAPAN.with(|ppn| ppn.to_string())
+    dengan_papan(|papan| papan.to_string())
+}
+
+/// Papan dalam bentuk Uint8Array, lihat Papan::kode untuk susunannya
+#[wasm_bindgen(js_name = kodePapan)]
+pub fn kode_papan() -> Vec<u8> {
+    dengan_papan(|papan| papan.kode())
+}
+
+/// Identitas permen di setiap petak sebagai Uint32Array, untuk menggerakkan
+/// elemen yang sama ketika memainkan tahap animasi
+#[wasm_bindgen(js_name = idPapan)]
+pub fn id_papan() -> Vec<u32> {
+    dengan_papan(|papan| papan.semua_id())
 }
 
 #[wasm_bindgen(js_name = tukarPosisi)]
 pub fn tukar_posisi(y1: usize, x1: usize, y2: usize, x2: usize) {
-    PAPAN.with(|ppn| ppn.tukar((y1, x1), (y2, x2)) );
+    dengan_papan(|papan| papan.tukar((y1, x1), (y2, x2)) );
+}
+
+/// Memainkan satu giliran pada permainan bawaan, lihat Permainan::playMove
+#[wasm_bindgen(js_name = playMove)]
+pub fn play_move(y1: usize, x1: usize, y2: usize, x2: usize) -> JsValue {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().play_move(y1, x1, y2, x2))
+}
+
+/// Memajukan jam permainan bawaan pada mode berwaktu, lihat Permainan::tick
+#[wasm_bindgen]
+pub fn tick(delta_ms: f64) {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().tick(delta_ms))
+}
+
+/// Mengetuk permen pada permainan bawaan, lihat Permainan::ketuk
+#[wasm_bindgen(js_name = ketukPermen)]
+pub fn ketuk_permen(y: usize, x: usize) -> JsValue {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().ketuk(y, x))
+}
+
+/// Menggeser permen pada permainan bawaan, lihat Permainan::geser
+#[wasm_bindgen(js_name = geserPermen)]
+pub fn geser_permen(y: usize, x: usize, arah: &str) -> Result<JsValue, JsValue> {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().geser(y, x, arah))
+}
+
+/// Menekan tombol papan ketik pada permainan bawaan, lihat Permainan::tekanTombol
+#[wasm_bindgen(js_name = tekanTombol)]
+pub fn tekan_tombol(tombol: &str) -> Option<js_sys::Object> {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().tekan_tombol(tombol))
+}
+
+#[wasm_bindgen(js_name = kursorPapan)]
+pub fn kursor_papan() -> Vec<u32> {
+    PERMAINAN.with(|permainan| permainan.borrow().kursor())
 }
 
 #[wasm_bindgen(js_name = cekDempet)]
@@ -26,7 +106,7 @@ pub fn cek_dempet(y: usize, x: usize, tk: usize) {
     DEMPET.with(|dmpt| {
         dmpt.borrow_mut()
             .tambah_vek(
-                PAPAN.with(|ppn| ppn.cek_dempet((y, x), tk)))
+                dengan_papan(|papan| papan.cek_dempet((y, x), tk)))
             .urutkan();
     });
 }
@@ -40,7 +120,7 @@ pub fn panjang_dempet() -> usize {
 pub fn remukan_permen() {
     DEMPET.with(|dmpt| {
         for pos in dmpt.borrow().iter() {
-            PAPAN.with(|ppn| ppn.remukan(*pos))
+            dengan_papan(|papan| papan.remukan(*pos))
         }
         dmpt.borrow_mut().kosongkan();
     });
@@ -48,15 +128,117 @@ pub fn remukan_permen() {
 
 #[wasm_bindgen(js_name = bisaJalan)]
 pub fn bisa_jalan() -> bool {
-    PAPAN.with(|ppn| {
-        if ppn.cek_kemungkinan().len() > 0 {
-            return true
-        }
-        false
-    })
+    dengan_papan(|papan| !papan.cek_kemungkinan().is_empty())
 }
 
 #[wasm_bindgen(js_name = cekKemungkinan)]
 pub fn cek_kemungkinan() -> String {
-    PAPAN.with(|ppn| ppn.cek_kemungkinan_str())
+    dengan_papan(|papan| papan.cek_kemungkinan_str())
+}
+
+#[wasm_bindgen(js_name = muatLevel)]
+pub fn muat_level(teks: &str) -> Result<(), JsValue> {
+    ganti_permainan(Permainan::dari_level(teks, BATAS_RIWAYAT)?);
+    Ok(())
+}
+
+#[wasm_bindgen(js_name = mulaiTanpaAkhir)]
+pub fn mulai_tanpa_akhir(ukuran: usize) {
+    ganti_permainan(Permainan::dari_pilihan(Pilihan {
+        ukuran,
+        ragam_warna: 4,
+        mode: Mode::TanpaAkhir,
+        ..Pilihan::default()
+    }));
+}
+
+#[wasm_bindgen(js_name = tingkatKesulitan)]
+pub fn tingkat_kesulitan() -> Option<usize> {
+    PERMAINAN.with(|permainan| permainan.borrow().tingkat_kesulitan())
+}
+
+/// Membatalkan giliran terakhir, gagal jika riwayat dimatikan atau kosong
+#[wasm_bindgen]
+pub fn urungkan() -> Result<(), JsValue> {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().urungkan())
+}
+
+#[wasm_bindgen]
+pub fn ulangi() -> Result<(), JsValue> {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().ulangi())
+}
+
+/// Seluruh keadaan permainan sebagai string yang aman dipakai di URL atau localStorage
+#[wasm_bindgen(js_name = simpanPermainan)]
+pub fn simpan_permainan() -> String {
+    PERMAINAN.with(|permainan| permainan.borrow().simpanan())
+}
+
+/// Melanjutkan permainan dari hasil simpanPermainan
+#[wasm_bindgen(js_name = muatPermainan)]
+pub fn muat_permainan(teks: &str) -> Result<(), JsValue> {
+    ganti_permainan(Permainan::dari_simpanan(teks, BATAS_RIWAYAT)?);
+    Ok(())
+}
+
+/// Mengambil semua peristiwa yang belum diambil sebagai array objek { tipe, ... }
+#[wasm_bindgen(js_name = kurasPeristiwa)]
+pub fn kuras_peristiwa() -> js_sys::Array {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().kuras_peristiwa())
+}
+
+/// Memanggil pendengar untuk setiap peristiwa begitu terjadi,
+/// null untuk berhenti mendengarkan dan kembali memakai kurasPeristiwa
+#[wasm_bindgen(js_name = dengarkanPeristiwa)]
+pub fn dengarkan_peristiwa(pendengar: Option<js_sys::Function>) {
+    PERMAINAN.with(|permainan| permainan.borrow_mut().dengarkan_peristiwa(pendengar));
+}
+
+#[wasm_bindgen(js_name = levelTerbuka)]
+pub fn level_terbuka(indeks: usize) -> bool {
+    KAMPANYE.with(|kmp| kmp.borrow().terbuka(indeks))
+}
+
+#[wasm_bindgen(js_name = mulaiLevel)]
+pub fn mulai_level(indeks: usize) -> Result<(), JsValue> {
+    let level = KAMPANYE
+        .with(|kmp| kmp.borrow().ambil(indeks).cloned())
+        .map_err(galat_js)?;
+    ganti_permainan(Permainan::dengan_level(level, 0));
+    LEVEL_AKTIF.with(|aktif| aktif.set(Some(indeks)));
+    Ok(())
+}
+
+/// Mencatat hasil level kampanye yang sedang dimainkan setelah permainannya selesai,
+/// mengembalikan ringkasannya dalam bentuk string
+#[wasm_bindgen(js_name = selesaikanLevel)]
+pub fn selesaikan_level() -> Result<String, JsValue> {
+    let indeks = LEVEL_AKTIF
+        .with(|aktif| aktif.get())
+        .ok_or_else(|| JsValue::from_str("tidak ada level kampanye yang sedang dimainkan"))?;
+    if !PERMAINAN.with(|permainan| permainan.borrow().selesai()) {
+        return Err(JsValue::from_str("level kampanye belum selesai dimainkan"));
+    }
+    LEVEL_AKTIF.with(|aktif| aktif.set(None));
+    let ringkasan = PERMAINAN.with(|permainan| permainan.borrow().sesi().ringkasan());
+    KAMPANYE
+        .with(|kmp| kmp.borrow_mut().catat(indeks, ringkasan.clone()))
+        .map_err(galat_js)?;
+    Ok(ringkasan.to_string())
+}
+
+/// Catatan kampanye dalam bentuk string untuk disimpan di localStorage
+#[wasm_bindgen(js_name = catatanKampanye)]
+pub fn catatan_kampanye() -> String {
+    let mut penyimpanan = PenyimpananMemori::default();
+    KAMPANYE.with(|kmp| kmp.borrow().simpan(&mut penyimpanan));
+    penyimpanan.muat().unwrap_or_default()
+}
+
+#[wasm_bindgen(js_name = muatCatatanKampanye)]
+pub fn muat_catatan_kampanye(isi: Option<String>) -> Result<(), JsValue> {
+    let penyimpanan = PenyimpananMemori { isi };
+    KAMPANYE
+        .with(|kmp| kmp.borrow_mut().muat(&penyimpanan))
+        .map_err(galat_js)
 }
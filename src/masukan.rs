@@ -0,0 +1,280 @@
+//! Pengendali masukan yang dipakai bersama oleh tampilan sentuh dan papan ketik.
+//! Pengendali hanya mengubah pilihan dan meminta penukaran, memainkan gilirannya
+//! tetap tugas Sesi.
+
+use std::str::FromStr;
+
+use crate::permenremuk::{bersebelahan, Papan, Posisi};
+
+/// Arah geseran dari sebuah permen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arah {
+    Atas,
+    Bawah,
+    Kiri,
+    Kanan,
+}
+
+impl Arah {
+    /// Posisi tetangga dari pos ke arah ini, None jika keluar dari papan
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::masukan::Arah;
+    /// assert_eq!(Arah::Kanan.dari((2, 3), 7), Some((2, 4)));
+    /// assert_eq!(Arah::Atas.dari((0, 3), 7), None);
+    /// ```
+    pub fn dari(self, pos: Posisi, ukuran: usize) -> Option<Posisi> {
+        let (y, x) = pos;
+        let tetangga = match self {
+            Arah::Atas => (y.checked_sub(1)?, x),
+            Arah::Bawah => (y + 1, x),
+            Arah::Kiri => (y, x.checked_sub(1)?),
+            Arah::Kanan => (y, x + 1),
+        };
+        (tetangga.0 < ukuran && tetangga.1 < ukuran).then_some(tetangga)
+    }
+
+    /// Seperti dari, tetapi berputar ke sisi seberang ketika keluar dari papan
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::masukan::Arah;
+    /// assert_eq!(Arah::Atas.putar((0, 3), 7), (6, 3));
+    /// assert_eq!(Arah::Kanan.putar((2, 6), 7), (2, 0));
+    /// ```
+    pub fn putar(self, pos: Posisi, ukuran: usize) -> Posisi {
+        let (y, x) = pos;
+        match self {
+            Arah::Atas => ((y + ukuran - 1) % ukuran, x),
+            Arah::Bawah => ((y + 1) % ukuran, x),
+            Arah::Kiri => (y, (x + ukuran - 1) % ukuran),
+            Arah::Kanan => (y, (x + 1) % ukuran),
+        }
+    }
+}
+
+impl FromStr for Arah {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "atas" => Ok(Arah::Atas),
+            "bawah" => Ok(Arah::Bawah),
+            "kiri" => Ok(Arah::Kiri),
+            "kanan" => Ok(Arah::Kanan),
+            lain => Err(format!("arah tidak dikenal `{}`", lain)),
+        }
+    }
+}
+
+/// Masukan dari pemain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Masukan {
+    /// Mengetuk permen: memilih, membatalkan pilihan, atau menukar dengan permen terpilih
+    Ketuk(Posisi),
+    /// Memilih permen tanpa menukar, menggantikan pilihan sebelumnya
+    Pilih(Posisi),
+    /// Membatalkan pilihan
+    Lepas,
+    /// Menggeser permen pada posisi ke arah tertentu
+    Geser(Posisi, Arah),
+    /// Tombol panah: menggeser permen terpilih ke arah itu, atau memindahkan
+    /// kursor jika belum ada yang terpilih. Kursor berputar ke sisi seberang di tepi papan
+    Kursor(Arah),
+    /// Tombol Enter: sama seperti mengetuk permen di bawah kursor
+    KetukKursor,
+}
+
+impl Masukan {
+    /// Masukan untuk nama tombol seperti KeyboardEvent.key di peramban,
+    /// None untuk tombol yang tidak dipakai
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::masukan::{Arah, Masukan};
+    /// assert_eq!(Masukan::dari_tombol("ArrowLeft"), Some(Masukan::Kursor(Arah::Kiri)));
+    /// assert_eq!(Masukan::dari_tombol("q"), None);
+    /// ```
+    pub fn dari_tombol(tombol: &str) -> Option<Masukan> {
+        match tombol {
+            "ArrowUp" => Some(Masukan::Kursor(Arah::Atas)),
+            "ArrowDown" => Some(Masukan::Kursor(Arah::Bawah)),
+            "ArrowLeft" => Some(Masukan::Kursor(Arah::Kiri)),
+            "ArrowRight" => Some(Masukan::Kursor(Arah::Kanan)),
+            "Enter" | " " => Some(Masukan::KetukKursor),
+            "Escape" => Some(Masukan::Lepas),
+            _ => None,
+        }
+    }
+}
+
+/// Tanggapan pengendali atas sebuah masukan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tanggapan {
+    /// Pilihan berubah menjadi isi ini
+    Pilihan(Option<Posisi>),
+    /// Minta Sesi menukar dua permen ini, pilihan sudah dikosongkan
+    Tukar(Posisi, Posisi),
+    /// Kursor pindah ke posisi ini
+    Kursor(Posisi),
+    /// Masukan tidak mengubah apa pun
+    Abaikan,
+}
+
+/// Keadaan masukan pemain
+///
+/// ### Example
+/// ```
+/// # use crate::permen_remuk::permenremuk::Papan;
+/// # use crate::permen_remuk::masukan::{Masukan, Pengendali, Tanggapan};
+/// let papan = Papan::new(7, 7);
+/// let mut pengendali = Pengendali::default();
+/// assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((0, 2))), Tanggapan::Pilihan(Some((0, 2))));
+/// assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((1, 0))), Tanggapan::Pilihan(Some((1, 0))));
+/// assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((1, 1))), Tanggapan::Tukar((1, 0), (1, 1)));
+/// assert_eq!(pengendali.terpilih(), None);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pengendali {
+    terpilih: Option<Posisi>,
+    kursor: Posisi,
+}
+
+impl Pengendali {
+    pub fn terpilih(&self) -> Option<Posisi> {
+        self.terpilih
+    }
+
+    /// Posisi kursor papan ketik
+    pub fn kursor(&self) -> Posisi {
+        self.kursor
+    }
+
+    /// Mengolah satu masukan. Permen yang tidak bisa ditukar tidak bisa dipilih
+    pub fn terima(&mut self, papan: &Papan, masukan: Masukan) -> Tanggapan {
+        match masukan {
+            Masukan::Ketuk(pos) if !papan.bisa_ditukar(pos) => Tanggapan::Abaikan,
+            Masukan::Ketuk(pos) => match self.terpilih {
+                Some(terpilih) if terpilih == pos => self.ganti(None),
+                Some(terpilih) if bersebelahan(terpilih, pos) => {
+                    self.terpilih = None;
+                    Tanggapan::Tukar(terpilih, pos)
+                }
+                _ => self.ganti(Some(pos)),
+            },
+            Masukan::Pilih(pos) if !papan.bisa_ditukar(pos) => Tanggapan::Abaikan,
+            Masukan::Pilih(pos) => self.ganti(Some(pos)),
+            Masukan::Lepas => self.ganti(None),
+            Masukan::Kursor(arah) => match self.terpilih {
+                Some(terpilih) => self.terima(papan, Masukan::Geser(terpilih, arah)),
+                None => {
+                    self.kursor = arah.putar(self.kursor_di(papan), papan.ukuran);
+                    Tanggapan::Kursor(self.kursor)
+                }
+            },
+            Masukan::KetukKursor => {
+                self.kursor = self.kursor_di(papan);
+                self.terima(papan, Masukan::Ketuk(self.kursor))
+            }
+            Masukan::Geser(pos, arah) => {
+                let Some(tujuan) = arah.dari(pos, papan.ukuran) else { return Tanggapan::Abaikan };
+                if !papan.bisa_ditukar(pos) || !papan.bisa_ditukar(tujuan) {
+                    return Tanggapan::Abaikan;
+                }
+                self.terpilih = None;
+                Tanggapan::Tukar(pos, tujuan)
+            }
+        }
+    }
+
+    /// Mengosongkan pilihan, misalnya setelah papan diganti
+    pub fn kosongkan(&mut self) {
+        self.terpilih = None;
+    }
+
+    // Kursor bisa tertinggal di luar papan jika papan diganti dengan yang lebih kecil
+    fn kursor_di(&self, papan: &Papan) -> Posisi {
+        (self.kursor.0.min(papan.ukuran - 1), self.kursor.1.min(papan.ukuran - 1))
+    }
+
+    fn ganti(&mut self, terpilih: Option<Posisi>) -> Tanggapan {
+        if self.terpilih == terpilih {
+            return Tanggapan::Abaikan;
+        }
+        self.terpilih = terpilih;
+        Tanggapan::Pilihan(terpilih)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::permenremuk::Petak;
+
+    #[test]
+    fn masukan_ketuk() {
+        let papan = Papan::new(5, 5);
+        let mut pengendali = Pengendali::default();
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((0, 2))), Tanggapan::Pilihan(Some((0, 2))));
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((0, 2))), Tanggapan::Pilihan(None));
+        assert_eq!(pengendali.terima(&papan, Masukan::Lepas), Tanggapan::Abaikan);
+
+        // (0, 2) dan (1, 0) tidak bersebelahan walaupun jumlah koordinatnya berbeda satu
+        pengendali.terima(&papan, Masukan::Ketuk((0, 2)));
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((1, 0))), Tanggapan::Pilihan(Some((1, 0))));
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((0, 0))), Tanggapan::Tukar((1, 0), (0, 0)));
+        assert_eq!(pengendali.terpilih(), None);
+
+        papan.set_petak((4, 4), Petak::Kosong);
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((4, 4))), Tanggapan::Abaikan);
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((9, 0))), Tanggapan::Abaikan);
+        assert_eq!(pengendali.terima(&papan, Masukan::Pilih((4, 3))), Tanggapan::Pilihan(Some((4, 3))));
+        assert_eq!(pengendali.terima(&papan, Masukan::Ketuk((4, 4))), Tanggapan::Abaikan);
+        assert_eq!(pengendali.terpilih(), Some((4, 3)));
+    }
+
+    #[test]
+    fn masukan_geser() {
+        let papan = Papan::new(5, 5);
+        let mut pengendali = Pengendali::default();
+        pengendali.terima(&papan, Masukan::Pilih((2, 2)));
+        assert_eq!(pengendali.terima(&papan, Masukan::Geser((2, 2), Arah::Bawah)), Tanggapan::Tukar((2, 2), (3, 2)));
+        assert_eq!(pengendali.terpilih(), None);
+        assert_eq!(pengendali.terima(&papan, Masukan::Geser((0, 4), Arah::Kanan)), Tanggapan::Abaikan);
+        assert_eq!(pengendali.terima(&papan, Masukan::Geser((0, 0), Arah::Atas)), Tanggapan::Abaikan);
+
+        papan.set_petak((1, 1), Petak::Penghalang(2));
+        assert_eq!(pengendali.terima(&papan, Masukan::Geser((1, 0), Arah::Kanan)), Tanggapan::Abaikan);
+        assert_eq!("kiri".parse(), Ok(Arah::Kiri));
+        assert!("serong".parse::<Arah>().is_err());
+    }
+
+    #[test]
+    fn masukan_kursor() {
+        let papan = Papan::new(5, 5);
+        let mut pengendali = Pengendali::default();
+        assert_eq!(pengendali.kursor(), (0, 0));
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Kiri)), Tanggapan::Kursor((0, 4)));
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Atas)), Tanggapan::Kursor((4, 4)));
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Bawah)), Tanggapan::Kursor((0, 4)));
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Kanan)), Tanggapan::Kursor((0, 0)));
+        pengendali.terima(&papan, Masukan::Kursor(Arah::Bawah));
+
+        assert_eq!(pengendali.terima(&papan, Masukan::KetukKursor), Tanggapan::Pilihan(Some((1, 0))));
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Kiri)), Tanggapan::Abaikan);
+        assert_eq!(pengendali.terima(&papan, Masukan::Kursor(Arah::Kanan)), Tanggapan::Tukar((1, 0), (1, 1)));
+        assert_eq!(pengendali.kursor(), (1, 0));
+        assert_eq!(pengendali.terpilih(), None);
+
+        pengendali.terima(&papan, Masukan::KetukKursor);
+        assert_eq!(pengendali.terima(&papan, Masukan::dari_tombol("Escape").unwrap()), Tanggapan::Pilihan(None));
+        assert_eq!(pengendali.terima(&Papan::new(3, 3), Masukan::Kursor(Arah::Kanan)), Tanggapan::Kursor((1, 1)));
+
+        // Enter pada papan yang lebih kecil mengetuk permen di tepi papan
+        let mut pengendali = Pengendali::default();
+        pengendali.terima(&papan, Masukan::Kursor(Arah::Atas));
+        assert_eq!(pengendali.terima(&Papan::new(3, 3), Masukan::KetukKursor), Tanggapan::Pilihan(Some((2, 0))));
+        assert_eq!(pengendali.kursor(), (2, 0));
+    }
+}
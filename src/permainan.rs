@@ -0,0 +1,585 @@
+//! Pegangan permainan untuk JS. Setiap Permainan punya sesinya sendiri,
+//! sehingga satu halaman bisa memainkan beberapa papan sekaligus.
+//!
+//! ```js
+//! const permainan = new Permainan({ ukuran: 8, warna: 5, benih: 42, mode: "langkah", batas: 20 });
+//! permainan.tukar(3, 3, 3, 4);
+//! render(permainan.kode());
+//! ```
+//!
+//! Pilihan yang dikenal: `ukuran`, `warna` (jumlah warna, 3 sampai 7), `benih`, `riwayat`
+//! (jumlah giliran yang bisa diurungkan), dan `mode` yaitu `"bebas"`, `"langkah"`, `"waktu"`,
+//! atau `"tanpaAkhir"`. Mode langkah dan waktu butuh `batas` berupa jumlah langkah atau detik.
+
+use std::fmt::Display;
+
+use wasm_bindgen::prelude::*;
+
+use crate::level::Level;
+use crate::masukan::{Arah, Masukan, Pengendali, Tanggapan};
+use crate::permenremuk::*;
+
+pub(crate) fn galat_js(galat: impl Display) -> JsValue {
+    JsValue::from_str(&galat.to_string())
+}
+
+// Asal sesi, dipakai untuk memulai ulang permainan
+#[derive(Debug, Clone)]
+enum Asal {
+    Pilihan(Pilihan),
+    Level(Box<Level>, usize),
+    Simpanan(String, usize),
+}
+
+impl Asal {
+    fn buat_sesi(&self) -> Result<Sesi, Galat> {
+        let (sesi, batas_riwayat) = match self {
+            Asal::Pilihan(pilihan) => return Ok(pilihan.buat_sesi()),
+            Asal::Level(level, batas) => (level.buat_sesi(), *batas),
+            Asal::Simpanan(teks, batas) => (Sesi::dari_simpanan(teks)?, *batas),
+        };
+        Ok(match batas_riwayat {
+            0 => sesi,
+            batas => sesi.dengan_riwayat(batas),
+        })
+    }
+}
+
+/// Seluruh akibat dari satu giliran yang dimainkan lewat Permainan::mainkan
+#[derive(Debug, Clone, PartialEq)]
+pub struct HasilMain {
+    /// Hasil giliran, atau alasan penukaran ditolak. Papan tidak berubah jika ditolak
+    pub hasil: Result<HasilGiliran, Galat>,
+    /// Semua peristiwa selama giliran ini, berurutan
+    pub peristiwa: Vec<Peristiwa>,
+}
+
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct Permainan {
+    asal: Asal,
+    pub(crate) sesi: Sesi,
+    pub(crate) pendengar: Option<js_sys::Function>,
+    pengendali: Pengendali,
+}
+
+impl Permainan {
+    fn baru(asal: Asal, sesi: Sesi) -> Permainan {
+        Permainan { asal, sesi, pendengar: None, pengendali: Pengendali::default() }
+    }
+
+    pub fn dari_pilihan(pilihan: Pilihan) -> Permainan {
+        let sesi = pilihan.buat_sesi();
+        Permainan::baru(Asal::Pilihan(pilihan), sesi)
+    }
+
+    pub fn dengan_level(level: Level, batas_riwayat: usize) -> Permainan {
+        let asal = Asal::Level(Box::new(level), batas_riwayat);
+        let sesi = asal.buat_sesi().expect("level selalu bisa dibuat sesinya");
+        Permainan::baru(asal, sesi)
+    }
+
+    pub fn dengan_simpanan(teks: &str, batas_riwayat: usize) -> Result<Permainan, Galat> {
+        let asal = Asal::Simpanan(teks.to_string(), batas_riwayat);
+        let sesi = asal.buat_sesi()?;
+        Ok(Permainan::baru(asal, sesi))
+    }
+
+    pub fn sesi(&self) -> &Sesi {
+        &self.sesi
+    }
+
+    /// Memainkan satu giliran: memeriksa penukaran, menukar atau membatalkannya,
+    /// meremukan semua kaskade, lalu menghitung skornya.
+    /// Peristiwa yang sudah tertunda sebelum giliran ini tetap menunggu kuras_peristiwa
+    pub fn mainkan(&mut self, pos1: Posisi, pos2: Posisi) -> HasilMain {
+        let tertunda = self.sesi.peristiwa.len();
+        let hasil = self.sesi.tukar(pos1, pos2);
+        let peristiwa = self.sesi.peristiwa.split_off(tertunda);
+        if let Some(pendengar) = self.pendengar.as_ref() {
+            for peristiwa in peristiwa.iter() {
+                let _ = pendengar.call1(&JsValue::NULL, &peristiwa_js(peristiwa));
+            }
+        }
+        HasilMain { hasil, peristiwa }
+    }
+
+    /// Meneruskan masukan pemain ke pengendali, dan memainkan giliran
+    /// jika masukan itu meminta penukaran
+    pub fn kendalikan(&mut self, masukan: Masukan) -> (Tanggapan, Option<HasilMain>) {
+        let tanggapan = self.pengendali.terima(&self.sesi.papan, masukan);
+        let main = match tanggapan {
+            Tanggapan::Tukar(pos1, pos2) => Some(self.mainkan(pos1, pos2)),
+            _ => None,
+        };
+        (tanggapan, main)
+    }
+
+    fn kendalikan_js(&mut self, masukan: Masukan) -> JsValue {
+        let (_, main) = self.kendalikan(masukan);
+        let objek = js_sys::Object::new();
+        let terpilih = self.pengendali.terpilih().map_or(JsValue::NULL, posisi_js);
+        let _ = js_sys::Reflect::set(&objek, &"terpilih".into(), &terpilih);
+        let _ = js_sys::Reflect::set(&objek, &"kursor".into(), &posisi_js(self.pengendali.kursor()));
+        let main = main.map_or(JsValue::NULL, |main| self.main_js(&main));
+        let _ = js_sys::Reflect::set(&objek, &"main".into(), &main);
+        objek.into()
+    }
+
+    // Hasil giliran sebagai objek JS, lihat playMove
+    fn main_js(&self, main: &HasilMain) -> JsValue {
+        let objek = js_sys::Object::new();
+        let isi = |kunci: &str, nilai: JsValue| {
+            let _ = js_sys::Reflect::set(&objek, &kunci.into(), &nilai);
+        };
+        isi("sah", main.hasil.is_ok().into());
+        match &main.hasil {
+            Ok(hasil) => {
+                isi("galat", JsValue::NULL);
+                isi("skor", hasil.skor.into());
+                isi("fase", hasil.fase().iter().map(fase_js).collect::<js_sys::Array>().into());
+            }
+            Err(galat) => {
+                isi("galat", galat.to_string().into());
+                isi("skor", 0.into());
+                isi("fase", js_sys::Array::new().into());
+            }
+        }
+        isi("skorTotal", self.sesi.skor.into());
+        isi("langkah", self.sesi.langkah.into());
+        isi("sisaLangkah", self.sisa_langkah().into());
+        isi("sisaWaktu", self.sisa_waktu().into());
+        isi("selesai", self.sesi.selesai().into());
+        isi("peristiwa", main.peristiwa.iter().map(peristiwa_js).collect::<js_sys::Array>().into());
+        isi("papan", js_sys::Uint8Array::from(&self.kode()[..]).into());
+        isi("id", js_sys::Uint32Array::from(&self.id()[..]).into());
+        objek.into()
+    }
+
+    // Menjalankan aksi pada sesi, lalu mengirim peristiwa yang terjadi ke pendengar jika ada
+    pub(crate) fn jalankan<T>(&mut self, aksi: impl FnOnce(&mut Sesi) -> T) -> T {
+        let hasil = aksi(&mut self.sesi);
+        if let Some(pendengar) = self.pendengar.as_ref() {
+            for peristiwa in self.sesi.kuras_peristiwa() {
+                let _ = pendengar.call1(&JsValue::NULL, &peristiwa_js(&peristiwa));
+            }
+        }
+        hasil
+    }
+}
+
+// Membaca pilihan permainan dari objek JS, isian yang tidak ada memakai nilai bawaan
+fn pilihan_js(nilai: &JsValue) -> Result<Pilihan, JsValue> {
+    let mut pilihan = Pilihan::default();
+    if nilai.is_undefined() || nilai.is_null() {
+        return Ok(pilihan);
+    }
+    let baca = |kunci: &str| {
+        js_sys::Reflect::get(nilai, &kunci.into()).ok().filter(|isi| !isi.is_undefined() && !isi.is_null())
+    };
+    let angka = |kunci: &str| match baca(kunci) {
+        Some(isi) => isi
+            .as_f64()
+            .filter(|n| *n >= 0.0)
+            .map(Some)
+            .ok_or_else(|| galat_js(format!("pilihan `{}` harus berupa angka", kunci))),
+        None => Ok(None),
+    };
+
+    if let Some(ukuran) = angka("ukuran")? {
+        pilihan.ukuran = ukuran as usize;
+    }
+    if let Some(warna) = angka("warna")? {
+        pilihan.ragam_warna = warna as usize;
+    }
+    pilihan.benih = angka("benih")?.map(|benih| benih as u64);
+    if let Some(batas) = angka("riwayat")? {
+        pilihan.batas_riwayat = batas as usize;
+    }
+    let batas = angka("batas")?;
+    let butuh_batas = || galat_js("pilihan `batas` dibutuhkan untuk mode langkah dan waktu");
+    pilihan.mode = match baca("mode").and_then(|mode| mode.as_string()).as_deref() {
+        None | Some("bebas") => Mode::Bebas,
+        Some("langkah") => Mode::Langkah(batas.ok_or_else(butuh_batas)? as u32),
+        Some("waktu") => Mode::Waktu((batas.ok_or_else(butuh_batas)? * 1_000.0) as u64),
+        Some("tanpaAkhir") => Mode::TanpaAkhir,
+        Some(lain) => return Err(galat_js(format!("mode tidak dikenal `{}`", lain))),
+    };
+    Ok(pilihan)
+}
+
+#[wasm_bindgen]
+impl Permainan {
+    /// Membuat permainan baru, lihat dokumentasi modul untuk isi pilihan
+    #[wasm_bindgen(constructor)]
+    pub fn new(pilihan: JsValue) -> Result<Permainan, JsValue> {
+        Ok(Permainan::dari_pilihan(pilihan_js(&pilihan)?))
+    }
+
+    /// Membuat permainan dari teks level
+    #[wasm_bindgen(js_name = dariLevel)]
+    pub fn dari_level(teks: &str, batas_riwayat: usize) -> Result<Permainan, JsValue> {
+        let level: Level = teks.parse().map_err(galat_js)?;
+        Ok(Permainan::dengan_level(level, batas_riwayat))
+    }
+
+    /// Melanjutkan permainan dari hasil simpanan
+    #[wasm_bindgen(js_name = dariSimpanan)]
+    pub fn dari_simpanan(teks: &str, batas_riwayat: usize) -> Result<Permainan, JsValue> {
+        Permainan::dengan_simpanan(teks, batas_riwayat).map_err(galat_js)
+    }
+
+    /// Menukar dua permen bersebelahan dan meremukan semua dempet yang terbentuk,
+    /// mengembalikan skor yang didapat
+    pub fn tukar(&mut self, y1: usize, x1: usize, y2: usize, x2: usize) -> Result<u32, JsValue> {
+        self.jalankan(|sesi| sesi.tukar((y1, x1), (y2, x2)))
+            .map(|hasil| hasil.skor)
+            .map_err(galat_js)
+    }
+
+    /// Memainkan satu giliran dan mengembalikan seluruh akibatnya dalam satu objek:
+    /// `{ sah, galat, skor, skorTotal, langkah, sisaLangkah, sisaWaktu, selesai, fase, peristiwa, papan, id }`.
+    /// Penukaran yang ditolak tidak mengubah papan dan hanya mengisi `galat`.
+    /// Peristiwa giliran ini ikut dikembalikan sehingga tidak muncul lagi di kurasPeristiwa
+    #[wasm_bindgen(js_name = playMove)]
+    pub fn play_move(&mut self, y1: usize, x1: usize, y2: usize, x2: usize) -> JsValue {
+        let main = self.mainkan((y1, x1), (y2, x2));
+        self.main_js(&main)
+    }
+
+    /// Mengetuk permen, lihat Masukan::Ketuk. Mengembalikan `{ terpilih, kursor, main }` dengan
+    /// `terpilih` berupa [y, x] atau null dan `main` berupa hasil playMove jika terjadi penukaran
+    pub fn ketuk(&mut self, y: usize, x: usize) -> JsValue {
+        self.kendalikan_js(Masukan::Ketuk((y, x)))
+    }
+
+    /// Memilih permen tanpa menukar, hasilnya sama seperti ketuk
+    pub fn pilih(&mut self, y: usize, x: usize) -> JsValue {
+        self.kendalikan_js(Masukan::Pilih((y, x)))
+    }
+
+    pub fn lepas(&mut self) -> JsValue {
+        self.kendalikan_js(Masukan::Lepas)
+    }
+
+    /// Menggeser permen ke arah "atas", "bawah", "kiri", atau "kanan", hasilnya sama seperti ketuk
+    pub fn geser(&mut self, y: usize, x: usize, arah: &str) -> Result<JsValue, JsValue> {
+        let arah: Arah = arah.parse().map_err(galat_js)?;
+        Ok(self.kendalikan_js(Masukan::Geser((y, x), arah)))
+    }
+
+    /// Posisi permen terpilih sebagai [y, x], undefined jika tidak ada
+    #[wasm_bindgen(getter)]
+    pub fn terpilih(&self) -> Option<Vec<u32>> {
+        self.pengendali.terpilih().map(|(y, x)| vec![y as u32, x as u32])
+    }
+
+    /// Menekan tombol papan ketik dengan nama seperti KeyboardEvent.key: panah untuk
+    /// memindahkan kursor atau menggeser permen terpilih, Enter atau spasi untuk memilih,
+    /// Escape untuk membatalkan. Hasilnya sama seperti ketuk, ditambah `kursor`,
+    /// atau undefined untuk tombol yang tidak dipakai
+    #[wasm_bindgen(js_name = tekanTombol)]
+    pub fn tekan_tombol(&mut self, tombol: &str) -> Option<js_sys::Object> {
+        Masukan::dari_tombol(tombol).map(|masukan| self.kendalikan_js(masukan).into())
+    }
+
+    /// Posisi kursor papan ketik sebagai [y, x]
+    #[wasm_bindgen(getter)]
+    pub fn kursor(&self) -> Vec<u32> {
+        let (y, x) = self.pengendali.kursor();
+        vec![y as u32, x as u32]
+    }
+
+    /// Posisi permen yang bisa ditukar untuk petunjuk, berupa [y, x, y, x, ...]
+    pub fn kemungkinan(&self) -> Vec<u32> {
+        self.sesi.papan.cek_kemungkinan().into_iter().flat_map(|(y, x)| [y as u32, x as u32]).collect()
+    }
+
+    /// Papan dalam bentuk Uint8Array, lihat Papan::kode untuk susunannya
+    pub fn kode(&self) -> Vec<u8> {
+        self.sesi.papan.kode()
+    }
+
+    /// Identitas permen di setiap petak sebagai Uint32Array
+    pub fn id(&self) -> Vec<u32> {
+        self.sesi.papan.semua_id()
+    }
+
+    /// Papan dalam bentuk emoji
+    pub fn teks(&self) -> String {
+        self.sesi.papan.to_string()
+    }
+
+    /// Seluruh keadaan permainan sebagai string yang aman dipakai di URL atau localStorage
+    pub fn simpanan(&self) -> String {
+        self.sesi.simpanan()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn skor(&self) -> u32 {
+        self.sesi.skor
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn langkah(&self) -> u32 {
+        self.sesi.langkah
+    }
+
+    #[wasm_bindgen(getter, js_name = sisaLangkah)]
+    pub fn sisa_langkah(&self) -> Option<u32> {
+        self.sesi.sisa_langkah()
+    }
+
+    #[wasm_bindgen(getter, js_name = sisaWaktu)]
+    pub fn sisa_waktu(&self) -> Option<f64> {
+        self.sesi.sisa_waktu_ms.map(|ms| ms as f64)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn selesai(&self) -> bool {
+        self.sesi.selesai()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bintang(&self) -> u8 {
+        self.sesi.bintang()
+    }
+
+    #[wasm_bindgen(getter, js_name = tingkatKesulitan)]
+    pub fn tingkat_kesulitan(&self) -> Option<usize> {
+        self.sesi.kesulitan
+    }
+
+    /// Kemajuan sasaran dalam bentuk "label:nilai/target "
+    #[wasm_bindgen(getter)]
+    pub fn kemajuan(&self) -> String {
+        self.sesi.kemajuan_str()
+    }
+
+    /// Mengurangi sisa waktu pada mode berwaktu, pendengar diberi tahu ketika waktunya habis
+    pub fn tick(&mut self, delta_ms: f64) {
+        self.jalankan(|sesi| sesi.tick(delta_ms.max(0.0) as u64));
+    }
+
+    /// Membatalkan giliran terakhir, gagal jika riwayat dimatikan atau kosong
+    pub fn urungkan(&mut self) -> Result<(), JsValue> {
+        self.pengendali.kosongkan();
+        self.jalankan(|sesi| sesi.urungkan()).map_err(galat_js)
+    }
+
+    pub fn ulangi(&mut self) -> Result<(), JsValue> {
+        self.pengendali.kosongkan();
+        self.jalankan(|sesi| sesi.ulangi()).map_err(galat_js)
+    }
+
+    /// Memulai ulang permainan dengan pilihan yang sama. Tanpa benih,
+    /// papan baru diacak ulang
+    #[wasm_bindgen(js_name = mulaiUlang)]
+    pub fn mulai_ulang(&mut self) -> Result<(), JsValue> {
+        self.sesi = self.asal.buat_sesi().map_err(galat_js)?;
+        self.pengendali.kosongkan();
+        Ok(())
+    }
+
+    /// Mengambil semua peristiwa yang belum diambil sebagai array objek { tipe, ... }
+    #[wasm_bindgen(js_name = kurasPeristiwa)]
+    pub fn kuras_peristiwa(&mut self) -> js_sys::Array {
+        self.sesi.kuras_peristiwa().iter().map(peristiwa_js).collect()
+    }
+
+    /// Memanggil pendengar untuk setiap peristiwa begitu terjadi,
+    /// null untuk berhenti mendengarkan dan kembali memakai kurasPeristiwa
+    #[wasm_bindgen(js_name = dengarkanPeristiwa)]
+    pub fn dengarkan_peristiwa(&mut self, pendengar: Option<js_sys::Function>) {
+        self.pendengar = pendengar;
+    }
+}
+
+fn posisi_js(pos: Posisi) -> JsValue {
+    js_sys::Array::of2(&(pos.0 as u32).into(), &(pos.1 as u32).into()).into()
+}
+
+// Tahap animasi sebagai objek JS { tipe, gerak: [{ id, dari, ke }] }
+fn fase_js(fase: &Fase) -> JsValue {
+    let tipe = match fase {
+        Fase::Tukar(_) => "Tukar",
+        Fase::Remuk(_) => "Remuk",
+        Fase::Jatuh(_) => "Jatuh",
+        Fase::Muncul(_) => "Muncul",
+    };
+    let gerak: js_sys::Array = fase
+        .gerak()
+        .iter()
+        .map(|gerak| {
+            let objek = js_sys::Object::new();
+            let _ = js_sys::Reflect::set(&objek, &"id".into(), &gerak.id.into());
+            let dari = js_sys::Array::of2(&(gerak.dari.0 as i32).into(), &(gerak.dari.1 as u32).into());
+            let _ = js_sys::Reflect::set(&objek, &"dari".into(), &dari);
+            let _ = js_sys::Reflect::set(&objek, &"ke".into(), &posisi_js(gerak.ke));
+            JsValue::from(objek)
+        })
+        .collect();
+    let objek = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&objek, &"tipe".into(), &tipe.into());
+    let _ = js_sys::Reflect::set(&objek, &"gerak".into(), &gerak.into());
+    objek.into()
+}
+
+// Peristiwa sebagai objek JS { tipe, ... }. Warna dan jenis permen ditulis
+// dengan kode yang sama seperti kodePapan
+fn peristiwa_js(peristiwa: &Peristiwa) -> JsValue {
+    let objek = js_sys::Object::new();
+    let isi = |kunci: &str, nilai: JsValue| {
+        let _ = js_sys::Reflect::set(&objek, &kunci.into(), &nilai);
+    };
+    let tipe = match peristiwa {
+        Peristiwa::TukarDicoba(dari, ke) => {
+            isi("dari", posisi_js(*dari));
+            isi("ke", posisi_js(*ke));
+            "TukarDicoba"
+        }
+        Peristiwa::TukarDitolak(dari, ke, galat) => {
+            isi("dari", posisi_js(*dari));
+            isi("ke", posisi_js(*ke));
+            isi("galat", galat.to_string().into());
+            "TukarDitolak"
+        }
+        Peristiwa::DempetDitemukan(kelompok) => {
+            isi("warna", (kelompok.warna.indeks() as u32).into());
+            isi("posisi", kelompok.dempet.iter().map(|pos| posisi_js(*pos)).collect::<js_sys::Array>().into());
+            "DempetDitemukan"
+        }
+        Peristiwa::SpesialDibuat(pos, jenis) | Peristiwa::SpesialDipicu(pos, jenis) => {
+            isi("posisi", posisi_js(*pos));
+            isi("jenis", jenis.kode().into());
+            if matches!(peristiwa, Peristiwa::SpesialDibuat(..)) { "SpesialDibuat" } else { "SpesialDipicu" }
+        }
+        Peristiwa::PermenJatuh(dari, ke) => {
+            isi("dari", posisi_js(*dari));
+            isi("ke", posisi_js(*ke));
+            "PermenJatuh"
+        }
+        Peristiwa::PermenMuncul(pos, warna) => {
+            isi("posisi", posisi_js(*pos));
+            isi("warna", (warna.indeks() as u32).into());
+            "PermenMuncul"
+        }
+        Peristiwa::JeliDibersihkan(pos, sisa) => {
+            isi("posisi", posisi_js(*pos));
+            isi("sisa", (*sisa).into());
+            "JeliDibersihkan"
+        }
+        Peristiwa::PenghalangDipukul(pos) | Peristiwa::PenghalangHancur(pos) => {
+            isi("posisi", posisi_js(*pos));
+            if matches!(peristiwa, Peristiwa::PenghalangDipukul(_)) { "PenghalangDipukul" } else { "PenghalangHancur" }
+        }
+        Peristiwa::CokelatHancur(pos)
+        | Peristiwa::KunciDibuka(pos)
+        | Peristiwa::BahanTerkumpul(pos)
+        | Peristiwa::BomDijinakkan(pos)
+        | Peristiwa::BomMeledak(pos) => {
+            isi("posisi", posisi_js(*pos));
+            match peristiwa {
+                Peristiwa::CokelatHancur(_) => "CokelatHancur",
+                Peristiwa::KunciDibuka(_) => "KunciDibuka",
+                Peristiwa::BahanTerkumpul(_) => "BahanTerkumpul",
+                Peristiwa::BomDijinakkan(_) => "BomDijinakkan",
+                _ => "BomMeledak",
+            }
+        }
+        Peristiwa::CokelatMenyebar(asal, ditelan) => {
+            isi("dari", posisi_js(*asal));
+            isi("ke", posisi_js(*ditelan));
+            "CokelatMenyebar"
+        }
+        Peristiwa::TingkatKaskade(tingkat) => {
+            isi("tingkat", (*tingkat).into());
+            "TingkatKaskade"
+        }
+        Peristiwa::SkorBertambah(skor) => {
+            isi("skor", (*skor).into());
+            "SkorBertambah"
+        }
+        Peristiwa::KemajuanSasaran(semua) => {
+            let kemajuan: js_sys::Array = semua
+                .iter()
+                .map(|kemajuan| {
+                    js_sys::Array::of3(&kemajuan.sasaran.to_string().into(), &kemajuan.nilai.into(), &kemajuan.target.into())
+                })
+                .collect();
+            isi("kemajuan", kemajuan.into());
+            "KemajuanSasaran"
+        }
+        Peristiwa::Dikocok => "Dikocok",
+        Peristiwa::PermainanSelesai(ringkasan) => {
+            isi("skor", ringkasan.skor.into());
+            isi("bintang", ringkasan.bintang.into());
+            isi("ringkasan", ringkasan.to_string().into());
+            "PermainanSelesai"
+        }
+    };
+    isi("tipe", tipe.into());
+    objek.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permainan_mulai_ulang() {
+        let pilihan = Pilihan { benih: Some(5), mode: Mode::Langkah(10), ..Pilihan::default() };
+        let mut permainan = Permainan::dari_pilihan(pilihan.clone());
+        let lain = Permainan::dari_pilihan(Pilihan { benih: Some(6), ..pilihan });
+        let awal = permainan.kode();
+        assert_ne!(awal, lain.kode());
+
+        let (y, x) = permainan.sesi.papan.cek_kemungkinan()[0];
+        let tetangga = [(y, x + 1), (y + 1, x), (y, x.wrapping_sub(1)), (y.wrapping_sub(1), x)];
+        let berhasil = tetangga
+            .iter()
+            .any(|&(y2, x2)| permainan.jalankan(|sesi| sesi.tukar((y, x), (y2, x2))).is_ok());
+        assert!(berhasil);
+        assert_eq!(permainan.sisa_langkah(), Some(9));
+        assert_eq!(lain.sisa_langkah(), Some(10));
+
+        permainan.mulai_ulang().unwrap();
+        assert_eq!(permainan.kode(), awal);
+        assert_eq!((permainan.skor(), permainan.sisa_langkah()), (0, Some(10)));
+    }
+
+    #[test]
+    fn permainan_mainkan() {
+        let mut permainan = Permainan::dari_pilihan(Pilihan { benih: Some(11), ..Pilihan::default() });
+        let awal = permainan.kode();
+        // peristiwa yang tertunda sebelum giliran tidak ikut menjadi peristiwa giliran ini
+        assert!(permainan.sesi.tukar((0, 0), (0, 3)).is_err());
+        let ditolak = permainan.mainkan((0, 0), (2, 2));
+        assert_eq!(ditolak.hasil, Err(Galat::TidakBersebelahan((0, 0), (2, 2))));
+        assert_eq!(ditolak.peristiwa.len(), 2);
+        assert!(matches!(ditolak.peristiwa[0], Peristiwa::TukarDicoba(_, ke) if ke == (2, 2)));
+        let tertunda = permainan.sesi.kuras_peristiwa();
+        assert!(matches!(tertunda[..], [Peristiwa::TukarDicoba(_, (0, 3)), Peristiwa::TukarDitolak(..)]));
+        assert_eq!(permainan.kode(), awal);
+
+        let (y, x) = permainan.sesi.papan.cek_kemungkinan()[0];
+        let main = [(y, x + 1), (y + 1, x), (y, x.wrapping_sub(1)), (y.wrapping_sub(1), x)]
+            .iter()
+            .map(|&pos| permainan.mainkan((y, x), pos))
+            .find(|main| main.hasil.is_ok())
+            .unwrap();
+        let hasil = main.hasil.as_ref().unwrap();
+        assert!(matches!(main.peristiwa[0], Peristiwa::TukarDicoba(dari, _) if dari == (y, x)));
+        let skor: u32 = main
+            .peristiwa
+            .iter()
+            .map(|peristiwa| match peristiwa {
+                Peristiwa::SkorBertambah(skor) => *skor,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(skor, hasil.skor);
+        assert_eq!(permainan.skor(), hasil.skor);
+        assert!(permainan.sesi.kuras_peristiwa().is_empty());
+    }
+}
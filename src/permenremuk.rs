@@ -1,12 +1,15 @@
-use std::{cell::RefCell, ops::Deref};
+use std::{cell::{Cell, RefCell}, collections::VecDeque, ops::Deref};
 use std::cmp::max;
 use std::fmt::Display;
+use std::str::FromStr;
 use rand::{
-    distributions::{Distribution, Standard},
-    Rng
+    distributions::{Distribution, Standard, WeightedIndex},
+    seq::SliceRandom,
+    Rng, RngCore,
 };
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Warna {
     Merah,
     Jingga,
@@ -17,6 +20,16 @@ pub enum Warna {
     Ungu,
 }
 
+pub const SEMUA_WARNA: [Warna; 7] = [
+    Warna::Merah,
+    Warna::Jingga,
+    Warna::Kuning,
+    Warna::Hijau,
+    Warna::Biru,
+    Warna::Nila,
+    Warna::Ungu,
+];
+
 impl Distribution<Warna> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Warna {
         match rng.gen_range(0..7) {
@@ -31,6 +44,44 @@ impl Distribution<Warna> for Standard {
     }
 }
 
+impl Warna {
+    /// Urutan warna, dipakai sebagai indeks untuk hitungan per warna
+    pub fn indeks(&self) -> usize {
+        self.clone() as usize
+    }
+
+    /// Huruf depan nama warna, dipakai untuk menulis papan dalam bentuk teks
+    pub fn huruf(&self) -> char {
+        ['R', 'J', 'K', 'H', 'B', 'N', 'U'][self.indeks()]
+    }
+
+    /// Nama warna dalam huruf kecil
+    pub fn nama(&self) -> &'static str {
+        ["merah", "jingga", "kuning", "hijau", "biru", "nila", "ungu"][self.indeks()]
+    }
+}
+
+impl FromStr for Warna {
+    type Err = Galat;
+
+    /// Membaca warna dari namanya, huruf depannya, atau emoji-nya
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Warna;
+    /// assert_eq!("merah".parse(), Ok(Warna::Merah));
+    /// assert_eq!("J".parse(), Ok(Warna::Jingga));
+    /// assert_eq!("🟦".parse(), Ok(Warna::Biru));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SEMUA_WARNA
+            .iter()
+            .find(|warna| s == warna.nama() || s == warna.huruf().to_string() || s == warna.to_string())
+            .cloned()
+            .ok_or_else(|| Galat::FormatTidakSah(s.to_string()))
+    }
+}
+
 impl Display for Warna {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
@@ -45,20 +96,163 @@ impl Display for Warna {
     }
 }
 
+/// Jenis permen. Permen spesial dibuat dari dempet besar dan
+/// ikut meremukan permen di sekitarnya ketika diremukan
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Jenis {
+    #[default]
+    Biasa,
+    GarisMendatar,
+    GarisTegak,
+    Bungkus,
+    /// Bahan yang ikut jatuh tapi tidak pernah dempet dan tidak bisa diremukan,
+    /// terkumpul ketika sampai di petak keluar pada baris paling bawah
+    Bahan,
+    /// Permen pelangi yang cocok dengan warna apa pun, lihat Papan::semua_kelompok
+    Pelangi,
+}
+
+impl Jenis {
+    /// Jenis permen spesial yang dibuat dari sebuah Dempet,
+    /// 4 permen sebaris menjadi permen garis dan 5 atau lebih menjadi permen bungkus
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Dempet, Jenis};
+    /// # let mut dempet = Dempet::new();
+    /// dempet.tambah_vek(vec![(2, 0), (2, 1), (2, 2), (2, 3)]);
+    /// assert_eq!(Jenis::dari_dempet(&dempet), Some(Jenis::GarisMendatar));
+    /// ```
+    pub fn dari_dempet(dempet: &Dempet) -> Option<Jenis> {
+        match dempet.len() {
+            0..=3 => None,
+            4 if dempet.iter().all(|pos| pos.0 == dempet[0].0) => Some(Jenis::GarisMendatar),
+            4 => Some(Jenis::GarisTegak),
+            _ => Some(Jenis::Bungkus),
+        }
+    }
+
+    /// Posisi yang ikut diremukan ketika permen jenis ini diremukan di pos
+    pub fn wilayah(&self, pos: Posisi, ukuran: usize) -> Vec<Posisi> {
+        match self {
+            Jenis::Biasa | Jenis::Bahan | Jenis::Pelangi => vec![],
+            Jenis::GarisMendatar => (0..ukuran).map(|x| (pos.0, x)).collect(),
+            Jenis::GarisTegak => (0..ukuran).map(|y| (y, pos.1)).collect(),
+            Jenis::Bungkus => {
+                let mut wilayah = vec![];
+                for y in pos.0.saturating_sub(1)..=(pos.0 + 1).min(ukuran - 1) {
+                    for x in pos.1.saturating_sub(1)..=(pos.1 + 1).min(ukuran - 1) {
+                        wilayah.push((y, x));
+                    }
+                }
+                wilayah
+            }
+        }
+    }
+
+    /// Tanda jenis yang ditulis setelah huruf warna pada papan dalam bentuk teks.
+    /// Bahan dan pelangi tidak punya warna sehingga ditulis dengan tandanya saja
+    pub fn tanda(&self) -> &'static str {
+        ["", "-", "|", "*", "&", "$"][self.kode() as usize]
+    }
+
+    /// Apakah permen jenis ini ditulis tanpa warna
+    pub fn tanpa_warna(&self) -> bool {
+        matches!(self, Jenis::Bahan | Jenis::Pelangi)
+    }
+
+    /// Kode jenis untuk ekspor papan ke frontend
+    pub fn kode(&self) -> u8 {
+        self.clone() as u8
+    }
+
+    /// Kebalikan dari Jenis::kode
+    pub fn dari_kode(kode: u8) -> Option<Jenis> {
+        [Jenis::Biasa, Jenis::GarisMendatar, Jenis::GarisTegak, Jenis::Bungkus, Jenis::Bahan, Jenis::Pelangi]
+            .get(kode as usize)
+            .cloned()
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Permen {
     pub warna: RefCell<Warna>,
+    pub jenis: RefCell<Jenis>,
+    /// Identitas permen yang tetap sama selama permen berpindah di papan
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id: Cell<u32>,
+    /// Sisa giliran sebelum bom meledak, 0 jika permen ini bukan bom
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bom: Cell<u8>,
 }
 
 impl Display for Permen {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.warna.borrow())
+        match *self.jenis.borrow() {
+            Jenis::Bahan => write!(f, "🍒"),
+            Jenis::Pelangi => write!(f, "🌈"),
+            _ => write!(f, "{}", self.warna.borrow()),
+        }
     }
 }
 
 impl Permen {
     pub fn new(warna: Warna) -> Permen {
-        Permen { warna: RefCell::new(warna) }
+        Permen { warna: RefCell::new(warna), jenis: RefCell::new(Jenis::Biasa), id: Cell::new(0), bom: Cell::new(0) }
+    }
+
+    /// Mengambil identitas permen
+    pub fn get_id(&self) -> u32 {
+        self.id.get()
+    }
+
+    /// Mengambil jenis permen
+    pub fn get_jenis(&self) -> Jenis {
+        self.jenis.borrow().clone()
+    }
+
+    /// Menentukan jenis untuk permen
+    pub fn set_jenis(&self, jenis: Jenis) {
+        *self.jenis.borrow_mut() = jenis;
+    }
+
+    /// Sisa giliran bom, 0 jika permen ini bukan bom
+    pub fn get_bom(&self) -> u8 {
+        self.bom.get()
+    }
+
+    /// Menjadikan permen ini bom dengan sisa giliran tertentu, 0 untuk menjinakkannya
+    pub fn set_bom(&self, sisa: u8) {
+        self.bom.set(sisa);
+    }
+
+    /// Menyalin seluruh isi permen lain ke permen ini
+    pub fn salin(&self, lain: &Permen) {
+        self.set_warna(lain.get_warna());
+        self.set_jenis(lain.get_jenis());
+        self.id.set(lain.get_id());
+        self.bom.set(lain.get_bom());
+    }
+
+    /// Menukar seluruh isi permen ini dengan permen lain
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Jenis, Permen, Warna};
+    /// let permen = Permen::new(Warna::Merah);
+    /// let lain = Permen::new(Warna::Biru);
+    /// lain.set_jenis(Jenis::Bungkus);
+    /// permen.tukar(&lain);
+    /// assert_eq!(permen.get_warna(), Warna::Biru);
+    /// assert_eq!(permen.get_jenis(), Jenis::Bungkus);
+    /// ```
+    pub fn tukar(&self, lain: &Permen) {
+        self.warna.swap(&lain.warna);
+        self.jenis.swap(&lain.jenis);
+        self.id.swap(&lain.id);
+        self.bom.swap(&lain.bom);
     }
 
     /// Mengambil warna permen
@@ -92,21 +286,23 @@ impl Permen {
     /// ```
     /// # use crate::permen_remuk::permenremuk::{Permen, Warna};
     /// # let permen = Permen::new(Warna::Merah);
-    /// permen.acak_warna();
+    /// permen.acak_warna(&mut rand::thread_rng());
     /// # assert_ne!(permen.get_warna(), Warna::Merah);
     /// ```
-    pub fn acak_warna(&self) {
+    pub fn acak_warna<R: Rng + ?Sized>(&self, rng: &mut R) {
         let warna_awal = self.get_warna();
         let mut warna_akhir = self.get_warna();
 
         while warna_awal == warna_akhir {
-            self.set_warna(rand::random());
+            self.set_warna(rng.gen());
             warna_akhir = self.get_warna();
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Dempet {
     pub vektor: Vec<Posisi>
 }
@@ -135,7 +331,7 @@ impl Dempet {
     /// assert_eq!(dempet.len(), 1);
     /// ```
     pub fn tambah(&mut self, pos: Posisi) -> &mut Self {
-        if self.vektor.iter().any(|tup| *tup == pos) {
+        if self.vektor.contains(&pos) {
             return self;
         }
         self.vektor.push(pos);
@@ -203,6 +399,18 @@ impl Dempet {
         self.vektor.len()
     }
 
+    /// Apakah vektor Dempet kosong
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Dempet;
+    /// let dempet = Dempet::new();
+    /// assert!(dempet.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.vektor.is_empty()
+    }
+
     /// Mengambil mana saja kolom yang berubah
     ///
     /// ### Example
@@ -229,26 +437,268 @@ impl Dempet {
     }
 }
 
-const PAPAN_MIN: usize = 3;
-const PAPAN_MAX: usize = 10;
+pub const PAPAN_MIN: usize = 3;
+pub const PAPAN_MAX: usize = 10;
 
 type Isian = Vec<Vec<Permen>>;
-type Posisi = (usize, usize);
+pub type Posisi = (usize, usize);
 
-#[derive(Debug)]
+/// Apakah dua posisi bertetangga mendatar atau tegak
+pub fn bersebelahan(pos1: Posisi, pos2: Posisi) -> bool {
+    pos1.0.abs_diff(pos2.0) + pos1.1.abs_diff(pos2.1) == 1
+}
+
+// Permen yang jatuh (dari, ke), permen baru yang muncul, dan tahap animasinya
+type Jatuhan = (Vec<(Posisi, Posisi)>, Vec<(Posisi, Warna)>, Vec<Fase>);
+
+/// Jumlah pukulan paling banyak untuk menghancurkan satu penghalang
+pub const PENGHALANG_MAKS: u8 = 5;
+
+/// Keadaan sebuah petak di papan. Hanya petak Biasa yang berisi permen,
+/// petak lain menghentikan permen yang jatuh dan memutus deretan dempet
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Petak {
+    #[default]
+    Biasa,
+    /// Lubang, bukan bagian dari papan
+    Kosong,
+    /// Penghalang (lapisan gula atau es) dengan sisa jumlah pukulan sampai hancur,
+    /// 1 sampai PENGHALANG_MAKS. Setiap tingkat kaskade yang meremukan permen
+    /// di sebelahnya memukul penghalang satu kali
+    Penghalang(u8),
+    /// Cokelat yang hancur oleh permen remuk di sebelahnya. Setelah giliran
+    /// yang tidak menghancurkan cokelat, cokelat menelan satu permen di sebelahnya
+    Cokelat,
+}
+
+impl Petak {
+    /// Kode petak dan nilai tambahannya (sisa pukulan penghalang)
+    /// untuk ekspor papan ke frontend
+    pub fn kode(&self) -> (u8, u8) {
+        match *self {
+            Petak::Biasa => (0, 0),
+            Petak::Kosong => (1, 0),
+            Petak::Penghalang(nyawa) => (2, nyawa),
+            Petak::Cokelat => (3, 0),
+        }
+    }
+
+    /// Kebalikan dari Petak::kode
+    pub fn dari_kode(kode: u8, nilai: u8) -> Option<Petak> {
+        match (kode, nilai) {
+            (0, 0) => Some(Petak::Biasa),
+            (1, 0) => Some(Petak::Kosong),
+            (2, 1..=PENGHALANG_MAKS) => Some(Petak::Penghalang(nilai)),
+            (3, 0) => Some(Petak::Cokelat),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Petak {
+    /// Bentuk alternatif (`{:#}`) memakai tanda teks yang juga menulis sisa pukulan penghalang
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self, f.alternate()) {
+            (Petak::Biasa, _) => Ok(()),
+            (Petak::Kosong, false) => write!(f, "⬛"),
+            (Petak::Kosong, true) => write!(f, "."),
+            (Petak::Penghalang(_), false) => write!(f, "⬜"),
+            (Petak::Penghalang(nyawa), true) => write!(f, "#{}", nyawa),
+            (Petak::Cokelat, false) => write!(f, "🍫"),
+            (Petak::Cokelat, true) => write!(f, "%"),
+        }
+    }
+}
+
+/// Pembangkit bilangan acak (SplitMix64) yang keadaannya bisa disimpan,
+/// sehingga permainan yang dimuat ulang mendapat isian permen yang sama
+///
+/// ### Example
+/// ```
+/// # use crate::permen_remuk::permenremuk::Acak;
+/// # use rand::RngCore;
+/// let mut acak = Acak::new(42);
+/// let lain = acak.clone();
+/// let pertama = acak.next_u64();
+/// assert_eq!(Acak::new(lain.keadaan()).next_u64(), pertama);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Acak {
+    keadaan: u64,
+}
+
+impl Acak {
+    pub fn new(benih: u64) -> Acak {
+        Acak { keadaan: benih }
+    }
+
+    /// Keadaan saat ini, Acak::new(keadaan) melanjutkan urutan yang sama
+    pub fn keadaan(&self) -> u64 {
+        self.keadaan
+    }
+}
+
+impl RngCore for Acak {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.keadaan = self.keadaan.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.keadaan;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for potongan in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            potongan.copy_from_slice(&bytes[..potongan.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Jumlah byte untuk setiap petak pada hasil Papan::kode
+pub const KODE_PER_PETAK: usize = 7;
+/// Jumlah lapisan jeli paling banyak pada satu petak
+pub const JELI_MAKS: u8 = 2;
+/// Kode warna untuk petak yang tidak berisi permen
+pub const KODE_TANPA_PERMEN: u8 = u8::MAX;
+/// Bit pada byte tanda Papan::kode untuk permen yang terkunci kandang
+pub const TANDA_KUNCI: u8 = 1;
+/// Bit pada byte tanda Papan::kode untuk petak keluar bahan
+pub const TANDA_KELUAR: u8 = 2;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "PapanSerde"))]
 pub struct Papan {
     pub ukuran: usize,
     pub ragam_warna: usize,
-    pub isi: Isian
+    pub isi: Isian,
+    pub petak: Vec<Vec<RefCell<Petak>>>,
+    pub palet: Vec<(Warna, u32)>,
+    /// Sumber acak untuk isian permen baru, penghalang, dan kocokan
+    pub acak: RefCell<Acak>,
+    /// Identitas untuk permen baru berikutnya
+    pub id_berikutnya: Cell<u32>,
+    /// Jumlah lapisan jeli di bawah permen setiap petak, tidak ikut jatuh bersama permen
+    pub jeli: Vec<Vec<Cell<u8>>>,
+    /// Petak yang permennya terkunci kandang, permen itu tidak bisa ditukar dan tidak jatuh
+    pub kunci: Vec<Vec<Cell<bool>>>,
+    /// Kolom yang petak paling bawahnya menjadi jalan keluar bahan
+    pub keluar: Vec<usize>,
+    /// Jumlah bahan yang akan muncul pada isian permen baru berikutnya
+    pub bahan_tertunda: Cell<u32>,
+    /// Sisa giliran bom yang akan muncul pada isian permen baru berikutnya, diambil dari belakang
+    pub bom_tertunda: RefCell<Vec<u8>>,
+}
+
+// Bentuk Papan ketika dibaca serde. JSON lama belum punya isian yang ditambahkan kemudian,
+// isian itu memakai nilai bawaan dan petak jeli serta kunci dibuat sesuai ukuran papan
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PapanSerde {
+    ukuran: usize,
+    ragam_warna: usize,
+    isi: Isian,
+    petak: Vec<Vec<RefCell<Petak>>>,
+    palet: Vec<(Warna, u32)>,
+    acak: RefCell<Acak>,
+    #[serde(default)]
+    id_berikutnya: Option<Cell<u32>>,
+    #[serde(default)]
+    jeli: Vec<Vec<Cell<u8>>>,
+    #[serde(default)]
+    kunci: Vec<Vec<Cell<bool>>>,
+    #[serde(default)]
+    keluar: Vec<usize>,
+    #[serde(default)]
+    bahan_tertunda: Cell<u32>,
+    #[serde(default)]
+    bom_tertunda: RefCell<Vec<u8>>,
+}
+
+#[cfg(feature = "serde")]
+impl From<PapanSerde> for Papan {
+    fn from(papan: PapanSerde) -> Papan {
+        let ukuran = papan.ukuran;
+        let id_berikutnya = papan.id_berikutnya.unwrap_or_else(|| {
+            // permen dari JSON tanpa identitas diberi identitas baru
+            for (id, permen) in papan.isi.iter().flatten().enumerate() {
+                permen.id.set(id as u32);
+            }
+            Cell::new((ukuran * ukuran) as u32)
+        });
+        let jeli = if papan.jeli.is_empty() {
+            (0..ukuran).map(|_| (0..ukuran).map(|_| Cell::new(0)).collect()).collect()
+        } else {
+            papan.jeli
+        };
+        let kunci = if papan.kunci.is_empty() {
+            (0..ukuran).map(|_| (0..ukuran).map(|_| Cell::new(false)).collect()).collect()
+        } else {
+            papan.kunci
+        };
+        Papan {
+            ukuran,
+            ragam_warna: papan.ragam_warna,
+            isi: papan.isi,
+            petak: papan.petak,
+            palet: papan.palet,
+            acak: papan.acak,
+            id_berikutnya,
+            jeli,
+            kunci,
+            keluar: papan.keluar,
+            bahan_tertunda: papan.bahan_tertunda,
+            bom_tertunda: papan.bom_tertunda,
+        }
+    }
 }
 
 impl Display for Papan {
+    /// Bentuk biasa memakai emoji. Bentuk alternatif (`{:#}`) memakai huruf warna dan tanda
+    /// petak seperti tata letak level, sehingga jenis permen dan sisa pukulan penghalang ikut tertulis.
+    /// Bom diikuti `!` dan sisa gilirannya, permen terkunci diikuti `@`,
+    /// lalu setiap lapisan jeli di bawah permen ditulis sebagai `~`
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Papan;
+    /// let papan: Papan = "R- J K~\nK R@ J!3\nJ K~~ #2".parse().unwrap();
+    /// assert_eq!(papan.to_string(), "🟥 🟧 🟨~ \n🟨 🟥@ 🟧!3 \n🟧 🟨~~ ⬜ \n");
+    /// assert_eq!(format!("{:#}", papan), "R- J K~ \nK R@ J!3 \nJ K~~ #2 \n");
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for baris in self.isi.iter() {
-            for permen in baris {
-                write!(f, "{} ", permen)?;
+        for (y, baris) in self.isi.iter().enumerate() {
+            for (x, permen) in baris.iter().enumerate() {
+                match *self.petak[y][x].borrow() {
+                    Petak::Biasa if f.alternate() && permen.get_jenis().tanpa_warna() => {
+                        write!(f, "{}", permen.get_jenis().tanda())?
+                    }
+                    Petak::Biasa if f.alternate() => {
+                        write!(f, "{}{}", permen.get_warna().huruf(), permen.get_jenis().tanda())?
+                    }
+                    Petak::Biasa => write!(f, "{}", permen)?,
+                    ref petak if f.alternate() => write!(f, "{:#}", petak)?,
+                    ref petak => write!(f, "{}", petak)?,
+                }
+                if self.get_bom((y, x)) > 0 {
+                    write!(f, "!{}", self.get_bom((y, x)))?;
+                }
+                let kunci = if self.kunci[y][x].get() { "@" } else { "" };
+                write!(f, "{}{} ", kunci, "~".repeat(self.jeli[y][x].get() as usize))?;
             }
-            write!(f, "\n")?;
+            writeln!(f)?;
         }
         Ok(())
     }
@@ -256,20 +706,77 @@ impl Display for Papan {
 
 impl Papan {
     pub fn new(ukuran: usize, ragam_warna: usize) -> Papan {
+        Papan::dengan_benih(ukuran, ragam_warna, rand::random())
+    }
+
+    /// Membuat papan yang isiannya ditentukan oleh benih,
+    /// benih yang sama selalu menghasilkan permainan yang sama
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Papan;
+    /// let papan = Papan::dengan_benih(7, 5, 2022);
+    /// assert_eq!(papan.to_string(), Papan::dengan_benih(7, 5, 2022).to_string());
+    /// ```
+    pub fn dengan_benih(ukuran: usize, ragam_warna: usize, benih: u64) -> Papan {
         // Batasi ukuran papan
         let mut ukuran_papan: usize = max(ukuran, PAPAN_MIN);
         if ukuran > PAPAN_MAX { ukuran_papan = PAPAN_MAX };
 
-        let isi = Papan::isian_baru(ukuran_papan);
-        Papan { ukuran: ukuran_papan, ragam_warna, isi }
+        let mut acak = Acak::new(benih);
+        let isi = Papan::isian_baru(ukuran_papan, &mut acak);
+        let petak = (0..ukuran_papan)
+            .map(|_| (0..ukuran_papan).map(|_| RefCell::new(Petak::Biasa)).collect())
+            .collect();
+        for (id, permen) in isi.iter().flatten().enumerate() {
+            permen.id.set(id as u32);
+        }
+        let palet = SEMUA_WARNA.iter().map(|warna| (warna.clone(), 1)).collect();
+        let id_berikutnya = Cell::new((ukuran_papan * ukuran_papan) as u32);
+        let jeli = (0..ukuran_papan).map(|_| (0..ukuran_papan).map(|_| Cell::new(0)).collect()).collect();
+        let kunci = (0..ukuran_papan).map(|_| (0..ukuran_papan).map(|_| Cell::new(false)).collect()).collect();
+        let papan = Papan {
+            ukuran: ukuran_papan,
+            ragam_warna,
+            isi,
+            petak,
+            palet,
+            acak: RefCell::new(acak),
+            id_berikutnya,
+            jeli,
+            kunci,
+            keluar: vec![],
+            bahan_tertunda: Cell::new(0),
+            bom_tertunda: RefCell::new(vec![]),
+        };
+        // papan baru harus punya permen yang bisa ditukar
+        if papan.cek_kemungkinan().is_empty() {
+            papan.kocok();
+        }
+        papan
+    }
+
+    // Permen baru dengan warna acak dan identitas yang belum pernah dipakai,
+    // menjadi bahan jika masih ada bahan yang tertunda, atau bom jika masih ada bom yang tertunda
+    fn permen_baru(&self) -> Permen {
+        let permen = Permen::new(self.warna_acak());
+        if self.bahan_tertunda.get() > 0 {
+            self.bahan_tertunda.set(self.bahan_tertunda.get() - 1);
+            permen.set_jenis(Jenis::Bahan);
+        } else if let Some(sisa) = self.bom_tertunda.borrow_mut().pop() {
+            permen.set_bom(sisa);
+        }
+        permen.id.set(self.id_berikutnya.get());
+        self.id_berikutnya.set(self.id_berikutnya.get() + 1);
+        permen
     }
 
     /// Membuat isian baru agar tidak ada permen yang dempet
-    pub fn isian_baru(ukuran: usize) -> Isian {
+    pub fn isian_baru<R: Rng + ?Sized>(ukuran: usize, rng: &mut R) -> Isian {
         let isi: Isian =
             (0..ukuran).map(|_| {
-                (0..ukuran).map(move |_| {
-                    Permen::new(rand::random())
+                (0..ukuran).map(|_| {
+                    Permen::new(rng.gen())
                 }).collect()
             }).collect();
 
@@ -279,14 +786,14 @@ impl Papan {
                     loop {
                         if *isi[y][x-2].warna.borrow() != *isi[y][x-1].warna.borrow() { break }
                         if *isi[y][x-1].warna.borrow() != *isi[y][x].warna.borrow() { break }
-                        isi[y][x].acak_warna();
+                        isi[y][x].acak_warna(rng);
                     }
                 }
                 if y >= 2 {
                     loop {
                         if *isi[y-2][x].warna.borrow() != *isi[y-1][x].warna.borrow() { break }
                         if *isi[y-1][x].warna.borrow() != *isi[y][x].warna.borrow() { break }
-                        isi[y][x].acak_warna();
+                        isi[y][x].acak_warna(rng);
                     }
                 }
             }
@@ -295,6 +802,276 @@ impl Papan {
         isi
     }
 
+    /// Mengacak ulang warna permen di semua posisi yang diberikan menurut palet
+    /// sampai tidak ada permen dempet yang memuat posisi itu. Jika tetap ada yang dempet, papan dikocok.
+    /// Mengembalikan false jika permen di posisi itu masih dempet
+    pub fn acak_isian(&self, semua_posisi: &[Posisi]) -> bool {
+        for pos in semua_posisi {
+            self.isi[pos.0][pos.1].salin(&self.permen_baru());
+        }
+        let dempet_acak = || -> Vec<Dempet> {
+            self.semua_dempet()
+                .into_iter()
+                .filter(|dmpt| semua_posisi.iter().any(|pos| dmpt.contains(pos)))
+                .collect()
+        };
+        for _ in 0..100 {
+            let dempet = dempet_acak();
+            if dempet.is_empty() { return true }
+            // permen acak yang dempet diganti dengan warna yang tidak dempet di posisinya jika ada
+            for pos in semua_posisi.iter().filter(|pos| dempet.iter().any(|dmpt| dmpt.contains(pos))) {
+                let mut pilihan: Vec<Warna> = self.palet.iter().map(|(warna, _)| warna.clone()).collect();
+                pilihan.shuffle(&mut *self.acak.borrow_mut());
+                for warna in pilihan {
+                    self.set_warna(*pos, warna);
+                    if self.cek_dempet(*pos, 3).is_empty() { break }
+                }
+            }
+        }
+        self.kocok() || dempet_acak().is_empty()
+    }
+
+    /// Mengambil warna acak dari palet sesuai bobotnya
+    pub fn warna_acak(&self) -> Warna {
+        let bobot = WeightedIndex::new(self.palet.iter().map(|(_, bobot)| *bobot))
+            .expect("palet harus berisi bobot yang tidak nol");
+        self.palet[bobot.sample(&mut *self.acak.borrow_mut())].0.clone()
+    }
+
+    /// Mengambil keadaan petak pada Posisi pos
+    pub fn get_petak(&self, pos: Posisi) -> Petak {
+        self.petak[pos.0][pos.1].borrow().clone()
+    }
+
+    /// Menentukan keadaan petak pada Posisi pos
+    pub fn set_petak(&self, pos: Posisi, petak: Petak) {
+        *self.petak[pos.0][pos.1].borrow_mut() = petak;
+    }
+
+    /// Jumlah lapisan jeli pada Posisi pos
+    pub fn get_jeli(&self, pos: Posisi) -> u8 {
+        self.jeli[pos.0][pos.1].get()
+    }
+
+    /// Menentukan jumlah lapisan jeli pada Posisi pos, paling banyak JELI_MAKS
+    pub fn set_jeli(&self, pos: Posisi, lapisan: u8) {
+        self.jeli[pos.0][pos.1].set(lapisan.min(JELI_MAKS));
+    }
+
+    /// Jumlah seluruh lapisan jeli yang masih ada di papan
+    pub fn sisa_jeli(&self) -> u32 {
+        self.jeli.iter().flatten().map(|lapisan| lapisan.get() as u32).sum()
+    }
+
+    /// Apakah permen pada Posisi pos terkunci kandang
+    pub fn terkunci(&self, pos: Posisi) -> bool {
+        self.kunci[pos.0][pos.1].get()
+    }
+
+    /// Mengunci atau membuka kandang permen pada Posisi pos
+    pub fn set_kunci(&self, pos: Posisi, kunci: bool) {
+        self.kunci[pos.0][pos.1].set(kunci);
+    }
+
+    /// Apakah petak pada Posisi pos berisi bahan
+    pub fn punya_bahan(&self, pos: Posisi) -> bool {
+        self.punya_permen(pos) && self.get_jenis(pos) == Jenis::Bahan
+    }
+
+    /// Jumlah bahan yang ada di papan
+    pub fn jumlah_bahan(&self) -> u32 {
+        let semua = (0..self.ukuran).flat_map(|y| (0..self.ukuran).map(move |x| (y, x)));
+        semua.filter(|pos| self.punya_bahan(*pos)).count() as u32
+    }
+
+    /// Sisa giliran bom pada Posisi pos, 0 jika petak itu tidak berisi bom
+    pub fn get_bom(&self, pos: Posisi) -> u8 {
+        if !self.punya_permen(pos) { return 0 }
+        self.isi[pos.0][pos.1].get_bom()
+    }
+
+    /// Menjadikan permen pada Posisi pos bom dengan sisa giliran tertentu, 0 untuk menjinakkannya
+    pub fn set_bom(&self, pos: Posisi, sisa: u8) {
+        self.isi[pos.0][pos.1].set_bom(sisa)
+    }
+
+    /// Jumlah bom yang ada di papan
+    pub fn jumlah_bom(&self) -> u32 {
+        let semua = (0..self.ukuran).flat_map(|y| (0..self.ukuran).map(move |x| (y, x)));
+        semua.filter(|pos| self.get_bom(*pos) > 0).count() as u32
+    }
+
+    /// Mengurangi sisa giliran setiap bom di papan.
+    /// Mengembalikan posisi bom yang sisa gilirannya habis, bom itu meledak
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Papan;
+    /// let papan: Papan = "R!2 J K\nK R J!1\nJ K R".parse().unwrap();
+    /// assert_eq!(papan.hitung_mundur_bom(), vec![(1, 2)]);
+    /// assert_eq!(papan.get_bom((0, 0)), 1);
+    /// assert_eq!(papan.jumlah_bom(), 1);
+    /// ```
+    pub fn hitung_mundur_bom(&self) -> Vec<Posisi> {
+        let mut meledak = vec![];
+        for y in 0..self.ukuran {
+            for x in 0..self.ukuran {
+                let sisa = self.get_bom((y, x));
+                if sisa == 0 { continue }
+                self.set_bom((y, x), sisa - 1);
+                if sisa == 1 {
+                    meledak.push((y, x));
+                }
+            }
+        }
+        meledak
+    }
+
+    /// Bahan yang sedang berada di petak keluar
+    pub fn bahan_di_keluar(&self) -> Vec<Posisi> {
+        self.keluar
+            .iter()
+            .filter(|x| **x < self.ukuran)
+            .map(|x| (self.ukuran - 1, *x))
+            .filter(|pos| self.punya_bahan(*pos))
+            .collect()
+    }
+
+    /// Apakah petak pada Posisi pos berisi permen
+    pub fn punya_permen(&self, pos: Posisi) -> bool {
+        *self.petak[pos.0][pos.1].borrow() == Petak::Biasa
+    }
+
+    /// Apakah permen pada Posisi pos boleh ditukar
+    pub fn bisa_ditukar(&self, pos: Posisi) -> bool {
+        pos.0 < self.ukuran && pos.1 < self.ukuran && self.punya_permen(pos) && !self.terkunci(pos)
+    }
+
+    /// Apakah petak pada Posisi pos menahan permen yang jatuh dari atasnya.
+    /// Permen terkunci tetap di tempatnya seperti penghalang
+    pub fn tetap(&self, pos: Posisi) -> bool {
+        !self.punya_permen(pos) || self.terkunci(pos)
+    }
+
+    /// Mengganti satu permen biasa yang dipilih acak dengan penghalang,
+    /// nyawa dibatasi 1 sampai PENGHALANG_MAKS. Bom tidak pernah tertutup penghalang
+    pub fn munculkan_penghalang(&self, nyawa: u8) -> Option<Posisi> {
+        let mut calon = vec![];
+        for y in 0..self.ukuran {
+            for x in 0..self.ukuran {
+                if self.bisa_ditukar((y, x)) && self.get_jenis((y, x)) == Jenis::Biasa && self.get_bom((y, x)) == 0 {
+                    calon.push((y, x));
+                }
+            }
+        }
+        if calon.is_empty() { return None }
+
+        let pos = calon[self.acak.borrow_mut().gen_range(0..calon.len())];
+        self.set_petak(pos, Petak::Penghalang(nyawa.clamp(1, PENGHALANG_MAKS)));
+        Some(pos)
+    }
+
+    /// Cokelat menelan satu permen acak yang bersebelahan dengannya, kecuali bahan dan bom.
+    /// Mengembalikan posisi cokelat asal dan permen yang ditelan,
+    /// None jika tidak ada cokelat yang bersebelahan dengan permen
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Petak};
+    /// let papan: Papan = "R J K\nK % J\nJ K R".parse().unwrap();
+    /// let (asal, ditelan) = papan.sebarkan_cokelat().unwrap();
+    /// assert_eq!(asal, (1, 1));
+    /// assert_eq!(papan.get_petak(ditelan), Petak::Cokelat);
+    /// ```
+    pub fn sebarkan_cokelat(&self) -> Option<(Posisi, Posisi)> {
+        let mut calon = vec![];
+        for y in 0..self.ukuran {
+            for x in 0..self.ukuran {
+                if self.get_petak((y, x)) != Petak::Cokelat { continue }
+                for tetangga in self.tetangga((y, x)) {
+                    if self.punya_permen(tetangga) && !self.punya_bahan(tetangga) && self.get_bom(tetangga) == 0 {
+                        calon.push(((y, x), tetangga));
+                    }
+                }
+            }
+        }
+        if calon.is_empty() { return None }
+
+        let (asal, ditelan) = calon[self.acak.borrow_mut().gen_range(0..calon.len())];
+        self.set_petak(ditelan, Petak::Cokelat);
+        self.set_kunci(ditelan, false);
+        Some((asal, ditelan))
+    }
+
+    /// Posisi yang bersebelahan (atas, bawah, kiri, kanan) dengan pos
+    pub fn tetangga(&self, pos: Posisi) -> Vec<Posisi> {
+        let mut tetangga = vec![];
+        if pos.0 > 0 { tetangga.push((pos.0 - 1, pos.1)) }
+        if pos.0 + 1 < self.ukuran { tetangga.push((pos.0 + 1, pos.1)) }
+        if pos.1 > 0 { tetangga.push((pos.0, pos.1 - 1)) }
+        if pos.1 + 1 < self.ukuran { tetangga.push((pos.0, pos.1 + 1)) }
+        tetangga
+    }
+
+    // Warna permen di pos ketika mencari deretan dempet: None jika petak itu tidak bisa
+    // dempet (tanpa permen atau berisi bahan), Some(None) untuk pelangi yang cocok dengan warna apa pun
+    fn warna_deret(&self, pos: Posisi) -> Option<Option<Warna>> {
+        if !self.punya_permen(pos) { return None }
+        match self.get_jenis(pos) {
+            Jenis::Bahan => None,
+            Jenis::Pelangi => Some(None),
+            _ => Some(Some(self.get_warna(pos))),
+        }
+    }
+
+    /// Semua deretan pada satu garis (baris atau kolom) beserta warnanya, termasuk yang lebih pendek dari 3.
+    /// Satu deretan adalah petak bersebelahan yang permennya berwarna sama atau pelangi.
+    /// Pelangi ikut memanjangkan deretan di kedua sisinya, sehingga pelangi di antara dua warna
+    /// berbeda masuk ke kedua deretan. Deretan yang hanya berisi pelangi tidak punya warna dan tidak dihitung
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Warna};
+    /// let papan: Papan = "R R $ J J\nJ K R K R\nK J K J K\nR K J R J\nJ R K J K".parse().unwrap();
+    /// let baris: Vec<_> = (0..5).map(|x| (0, x)).collect();
+    /// assert_eq!(papan.deret_garis(&baris), vec![
+    ///     (Warna::Merah, vec![(0, 0), (0, 1), (0, 2)]),
+    ///     (Warna::Jingga, vec![(0, 2), (0, 3), (0, 4)]),
+    /// ]);
+    /// ```
+    pub fn deret_garis(&self, garis: &[Posisi]) -> Vec<(Warna, Vec<Posisi>)> {
+        let mut hasil = vec![];
+        for ruas in garis.split(|pos| self.warna_deret(*pos).is_none()) {
+            let warna: Vec<Option<Warna>> = ruas.iter().map(|pos| self.warna_deret(*pos).flatten()).collect();
+            let mut idx = 0;
+            while idx < ruas.len() {
+                let Some(w) = warna[idx].clone() else {
+                    idx += 1;
+                    continue;
+                };
+                // mundur melewati pelangi, lalu maju selama warnanya sama atau pelangi
+                let mut awal = idx;
+                while awal > 0 && warna[awal - 1].is_none() { awal -= 1 }
+                let mut akhir = idx + 1;
+                while akhir < ruas.len() && warna[akhir].as_ref().is_none_or(|lain| *lain == w) { akhir += 1 }
+                hasil.push((w, ruas[awal..akhir].to_vec()));
+                idx = akhir;
+            }
+        }
+        hasil
+    }
+
+    // Gabungan deretan pada garis yang memuat pa dan panjangnya minimal tk
+    fn deret_di(&self, garis: &[Posisi], pa: Posisi, tk: usize) -> Vec<Posisi> {
+        let mut dempet = Dempet::new();
+        for (_, deret) in self.deret_garis(garis) {
+            if deret.len() >= tk && deret.contains(&pa) {
+                dempet.tambah_vek(deret);
+            }
+        }
+        dempet.vektor
+    }
+
     /// Mengambil warna permen pada Posisi pos
     pub fn get_warna(&self, pos: Posisi) -> Warna {
         self.isi[pos.0][pos.1].get_warna()
@@ -305,7 +1082,28 @@ impl Papan {
         self.isi[pos.0][pos.1].set_warna(warna)
     }
 
-    /// Menukar warna dua permen yang bersebelahan
+    /// Mengambil jenis permen pada Posisi pos
+    pub fn get_jenis(&self, pos: Posisi) -> Jenis {
+        self.isi[pos.0][pos.1].get_jenis()
+    }
+
+    /// Mengambil identitas permen pada Posisi pos
+    pub fn get_id(&self, pos: Posisi) -> u32 {
+        self.isi[pos.0][pos.1].get_id()
+    }
+
+    /// Identitas permen di setiap petak baris demi baris, urutannya sama dengan Papan::kode.
+    /// Petak yang tidak berisi permen tetap punya identitas yang tidak dipakai
+    pub fn semua_id(&self) -> Vec<u32> {
+        self.isi.iter().flatten().map(Permen::get_id).collect()
+    }
+
+    /// Menentukan jenis permen pada Posisi pos
+    pub fn set_jenis(&self, pos: Posisi, jenis: Jenis) {
+        self.isi[pos.0][pos.1].set_jenis(jenis)
+    }
+
+    /// Menukar warna dua permen yang bersebelahan, permen terkunci tidak ikut ditukar
     ///
     /// ### Example
     /// ```
@@ -318,68 +1116,33 @@ impl Papan {
     /// ```
     pub fn tukar(&self, pos1: Posisi, pos2: Posisi) {
         // hanya permen yang bersebelahan yang bisa ditukar
-        if (pos1.0 as i16 - pos2.0 as i16).abs() > 1 || (pos1.1 as i16 - pos2.1 as i16).abs() > 1 {
-            return;
-        }
-        let p1 = self.isi[pos1.0][pos1.1].clone();
-        let p2 = self.isi[pos2.0][pos2.1].clone();
-        self.set_warna(pos1, p2.get_warna());
-        self.set_warna(pos2, p1.get_warna());
+        if !bersebelahan(pos1, pos2) || self.terkunci(pos1) || self.terkunci(pos2) { return }
+        self.isi[pos1.0][pos1.1].tukar(&self.isi[pos2.0][pos2.1]);
     }
 
     /// Meremukan permen dengan mengganti permen dengan permen di atasnya
+    /// Permen hanya jatuh di dalam ruas kolomnya, petak yang tetap menahan
+    /// permen di atasnya dan permen baru muncul di puncak ruas
     pub fn remukan(&self, pos: Posisi) {
-        let mut ps = pos.clone();
-        while ps.0 != 0 {
-            // ganti warna permen dengan warna permen di atas permen tersebut
-            self.set_warna((ps.0, pos.1), self.get_warna((ps.0-1, pos.1)));
+        let mut ps = pos;
+        while ps.0 != 0 && !self.tetap((ps.0-1, pos.1)) {
+            // ganti permen dengan permen di atas permen tersebut
+            self.isi[ps.0][pos.1].salin(&self.isi[ps.0-1][pos.1]);
             ps.0 -= 1;
         }
-        self.set_warna((0, pos.1), rand::random());
+        self.isi[ps.0][pos.1].salin(&self.permen_baru());
     }
 
     // Mengecek permen "dempet" horizontal
     fn cek_horizontal(&self, pa: Posisi, tk: usize) -> Vec<Posisi> {
-        let mut temp = Vec::<Posisi>::new();
-        for idx in 0..self.ukuran {
-            if self.get_warna((pa.0, idx)) == self.get_warna(pa) {
-                temp.push((pa.0, idx));
-                continue;
-            }
-            if idx > pa.1 { break }
-            if temp.len() != 0 && temp.len() < tk { temp = vec![] }
-            else {
-                if temp.iter().any(|tup| *tup == pa) { break }
-                else {
-                    temp = vec![];
-                    continue;
-                }
-            }
-        }
-        if temp.len() < tk { temp = vec![] }
-        temp
+        let baris: Vec<Posisi> = (0..self.ukuran).map(|x| (pa.0, x)).collect();
+        self.deret_di(&baris, pa, tk)
     }
 
     // Mengecek permen "dempet" vertikal
     fn cek_vertikal(&self, pa: Posisi, tk: usize) -> Vec<Posisi> {
-        let mut temp = Vec::<Posisi>::new();
-        for idx in 0..self.ukuran {
-            if self.get_warna((idx, pa.1)) == self.get_warna(pa) {
-                temp.push((idx, pa.1));
-                continue;
-            }
-            if idx > pa.0 { break }
-            if temp.len() != 0 && temp.len() < tk { temp = vec![] }
-            else {
-                if temp.iter().any(|tup| *tup == pa) { break }
-                else {
-                    temp = vec![];
-                    continue;
-                }
-            }
-        }
-        if temp.len() < tk { temp = vec![] }
-        temp
+        let kolom: Vec<Posisi> = (0..self.ukuran).map(|y| (y, pa.1)).collect();
+        self.deret_di(&kolom, pa, tk)
     }
 
     /// Mengecek permen "dempet"
@@ -392,7 +1155,7 @@ impl Papan {
         let mut pd = Vec::<Posisi>::new();
         let mut temp_xy = temp_x;
         temp_xy.extend(temp_y);
-        if temp_xy.len() > 0 {
+        if !temp_xy.is_empty() {
             for pos in temp_xy {
                 if pos.0 == pa.0 && pos.1 == pa.1 { continue }
                 pd.push(pos);
@@ -407,13 +1170,16 @@ impl Papan {
     pub fn mungkin_ditukar(&self, pos: Posisi) -> bool {
         let mut mungkin = false;
 
+        if !self.bisa_ditukar(pos) { return mungkin }
+
         let tukar_dan_cek = |y, x| {
             let y0 = pos.0 as i16 + y;
             let x0 = pos.1 as i16 + x;
             let pos_target = (y0 as usize, x0 as usize);
+            if !self.bisa_ditukar(pos_target) { return false }
 
             self.tukar(pos, pos_target);
-            if self.cek_dempet(pos_target, 3).len() > 0 {
+            if !self.cek_dempet(pos_target, 3).is_empty() {
                 self.tukar(pos, pos_target);
                 return true
             }
@@ -463,16 +1229,1557 @@ impl Papan {
         }
         result
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    fn vektor_warna() -> Vec<Warna> {
-        vec![
-            Warna::Merah,
-            Warna::Jingga,
+    /// Papan dalam bentuk byte untuk frontend. Dua byte pertama adalah lebar dan tinggi,
+    /// lalu KODE_PER_PETAK byte untuk setiap petak baris demi baris:
+    /// kode petak, nilai petak, indeks warna (KODE_TANPA_PERMEN jika tidak ada permen),
+    /// kode jenis, jumlah lapisan jeli, byte tanda (TANDA_KUNCI dan TANDA_KELUAR), dan sisa giliran bom
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Petak, KODE_PER_PETAK, KODE_TANPA_PERMEN};
+    /// let papan = Papan::new(5, 3);
+    /// papan.set_petak((0, 1), Petak::Penghalang(2));
+    /// let kode = papan.kode();
+    /// assert_eq!(kode.len(), 2 + 5 * 5 * KODE_PER_PETAK);
+    /// assert_eq!(kode[..2], [5, 5]);
+    /// assert_eq!(kode[2 + KODE_PER_PETAK..][..3], [2, 2, KODE_TANPA_PERMEN]);
+    /// ```
+    pub fn kode(&self) -> Vec<u8> {
+        let mut kode = Vec::with_capacity(2 + self.ukuran * self.ukuran * KODE_PER_PETAK);
+        kode.extend([self.ukuran as u8, self.ukuran as u8]);
+        for y in 0..self.ukuran {
+            for x in 0..self.ukuran {
+                let (petak, nilai) = self.petak[y][x].borrow().kode();
+                kode.extend([petak, nilai]);
+                if self.punya_permen((y, x)) {
+                    kode.extend([self.get_warna((y, x)).indeks() as u8, self.get_jenis((y, x)).kode()]);
+                } else {
+                    kode.extend([KODE_TANPA_PERMEN, 0]);
+                }
+                let mut tanda = 0;
+                if self.terkunci((y, x)) { tanda |= TANDA_KUNCI }
+                if y + 1 == self.ukuran && self.keluar.contains(&x) { tanda |= TANDA_KELUAR }
+                kode.extend([self.get_jeli((y, x)), tanda, self.get_bom((y, x))]);
+            }
+        }
+        kode
+    }
+
+    /// Mencari semua permen dempet di papan. Deretan horizontal dan
+    /// vertikal yang saling bersinggungan digabung menjadi satu Dempet
+    pub fn semua_dempet(&self) -> Vec<Dempet> {
+        self.semua_kelompok().into_iter().map(|klp| klp.dempet).collect()
+    }
+
+    /// Mencari semua permen dempet di papan beserta warnanya.
+    /// Deretan horizontal dan vertikal berwarna sama yang bersinggungan digabung menjadi satu Kelompok.
+    /// Deretan berbeda warna yang berbagi permen pelangi tidak digabung karena warnanya tidak jelas,
+    /// masing-masing menjadi Kelompok sendiri dan pelangi itu ada di keduanya
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Warna};
+    /// let papan: Papan = "R R $ J J\nJ K R K R\nK J R J K\nR K J R J\nJ R K J K".parse().unwrap();
+    /// let kelompok = papan.semua_kelompok();
+    /// assert_eq!(kelompok.len(), 2);
+    /// assert_eq!((kelompok[0].warna.clone(), kelompok[0].dempet.len()), (Warna::Jingga, 3));
+    /// assert_eq!((kelompok[1].warna.clone(), kelompok[1].dempet.len()), (Warna::Merah, 5));
+    /// ```
+    pub fn semua_kelompok(&self) -> Vec<Kelompok> {
+        let baris: Vec<_> = (0..self.ukuran)
+            .map(|y| self.deret_garis(&(0..self.ukuran).map(|x| (y, x)).collect::<Vec<_>>()))
+            .collect();
+        let kolom: Vec<_> = (0..self.ukuran)
+            .map(|x| self.deret_garis(&(0..self.ukuran).map(|y| (y, x)).collect::<Vec<_>>()))
+            .collect();
+
+        // urutan deretan mengikuti petak pertama yang dilewatinya, baris demi baris
+        let mut semua_deret = Vec::<(Warna, Vec<Posisi>)>::new();
+        for (y, deret_baris) in baris.iter().enumerate() {
+            for (x, deret_kolom) in kolom.iter().enumerate() {
+                for deret in deret_baris.iter().chain(deret_kolom.iter()) {
+                    if deret.1.len() >= 3 && deret.1.contains(&(y, x)) && !semua_deret.contains(deret) {
+                        semua_deret.push(deret.clone());
+                    }
+                }
+            }
+        }
+
+        let mut hasil = Vec::<Kelompok>::new();
+        for (warna, deret) in semua_deret {
+            let mut dempet = Dempet::new();
+            dempet.tambah_vek(deret);
+            let (bersinggungan, terpisah): (Vec<Kelompok>, Vec<Kelompok>) = hasil
+                .into_iter()
+                .partition(|lain| lain.warna == warna && lain.dempet.iter().any(|pos| dempet.contains(pos)));
+            for lain in bersinggungan {
+                dempet.tambah_vek(lain.dempet.vektor);
+            }
+            dempet.urutkan();
+            hasil = terpisah;
+            hasil.push(Kelompok { warna, dempet });
+        }
+        hasil
+    }
+
+    /// Meremukan semua permen dempet berulang kali sampai papan stabil.
+    /// Setiap putaran dicatat sebagai satu tingkat Kaskade.
+    /// Permen spesial dari tingkat pertama diutamakan muncul di salah satu posisi asal
+    pub fn remukan_kaskade(&self, asal: &[Posisi]) -> Vec<Kaskade> {
+        self.lanjutkan_kaskade(vec![], asal)
+    }
+
+    /// Meremukan satu permen pilihan sebagai tingkat pertama,
+    /// lalu meremukan permen dempet yang terbentuk setelahnya
+    pub fn remukan_satu(&self, pos: Posisi) -> Vec<Kaskade> {
+        let mut remuk = Dempet::new();
+        remuk.tambah(pos);
+        let pertama = self.remukan_tingkat(1, vec![], vec![], remuk);
+        self.lanjutkan_kaskade(vec![pertama], &[])
+    }
+
+    fn lanjutkan_kaskade(&self, mut semua_kaskade: Vec<Kaskade>, asal: &[Posisi]) -> Vec<Kaskade> {
+        loop {
+            let kelompok = self.semua_kelompok();
+            if kelompok.is_empty() && self.bahan_di_keluar().is_empty() { break }
+
+            // tentukan permen spesial yang akan dibuat dari dempet besar
+            let mut spesial_dibuat = Vec::<(Posisi, Jenis)>::new();
+            for klp in kelompok.iter() {
+                let Some(jenis) = Jenis::dari_dempet(&klp.dempet) else { continue };
+                let biasa = |pos: &&Posisi| {
+                    self.get_jenis(**pos) == Jenis::Biasa && !self.terkunci(**pos) && self.get_bom(**pos) == 0
+                };
+                let pos = klp.dempet
+                    .iter()
+                    .filter(biasa)
+                    .find(|pos| semua_kaskade.is_empty() && asal.contains(*pos))
+                    .or_else(|| klp.dempet.iter().find(biasa));
+                if let Some(pos) = pos {
+                    spesial_dibuat.push((*pos, jenis));
+                }
+            }
+
+            let mut remuk = Dempet::new();
+            for klp in kelompok.iter() {
+                let sisa = klp.dempet
+                    .iter()
+                    .filter(|pos| !spesial_dibuat.iter().any(|(p, _)| p == *pos))
+                    .cloned()
+                    .collect();
+                remuk.tambah_vek(sisa);
+            }
+
+            let tingkat = semua_kaskade.len() as u32 + 1;
+            semua_kaskade.push(self.remukan_tingkat(tingkat, kelompok, spesial_dibuat, remuk));
+        }
+        semua_kaskade
+    }
+
+    // Meremukan permen di remuk beserta wilayah permen spesial yang terpicu,
+    // memukul penghalang di sekitarnya, mengumpulkan bahan di petak keluar,
+    // lalu menjatuhkan permen ke tempat yang kosong
+    fn remukan_tingkat(
+        &self,
+        tingkat: u32,
+        kelompok: Vec<Kelompok>,
+        spesial_dibuat: Vec<(Posisi, Jenis)>,
+        mut remuk: Dempet,
+    ) -> Kaskade {
+        let dibuat = |pos: &Posisi| spesial_dibuat.iter().any(|(p, _)| p == pos);
+
+        // permen terkunci yang ikut dempet tidak remuk, kandangnya yang terbuka
+        let mut kunci_dibuka = Dempet::new();
+        let (terkunci, bebas) = remuk.vektor.into_iter().partition(|pos| self.terkunci(*pos));
+        kunci_dibuka.tambah_vek(terkunci);
+        remuk = Dempet::new();
+        remuk.tambah_vek(bebas);
+
+        // permen spesial yang ikut remuk akan meremukan wilayahnya juga
+        let mut spesial_dipicu = Vec::<(Posisi, Jenis)>::new();
+        let mut penghalang_dipukul = Dempet::new();
+        let mut cokelat_hancur = Dempet::new();
+        let mut antrean = remuk.vektor.clone();
+        while let Some(pos) = antrean.pop() {
+            let jenis = self.get_jenis(pos);
+            if matches!(jenis, Jenis::Biasa | Jenis::Pelangi) { continue }
+            for target in jenis.wilayah(pos, self.ukuran) {
+                match self.get_petak(target) {
+                    Petak::Penghalang(_) => { penghalang_dipukul.tambah(target); }
+                    Petak::Cokelat => { cokelat_hancur.tambah(target); }
+                    _ => {}
+                }
+                if self.terkunci(target) {
+                    kunci_dibuka.tambah(target);
+                } else if self.punya_permen(target) && !self.punya_bahan(target) && !dibuat(&target) && !remuk.contains(&target) {
+                    remuk.tambah(target);
+                    antrean.push(target);
+                }
+            }
+            spesial_dipicu.push((pos, jenis));
+        }
+
+        // bom yang ikut remuk berhasil dijinakkan
+        let bom_dijinakkan: Vec<Posisi> = remuk.iter().filter(|pos| self.get_bom(**pos) > 0).cloned().collect();
+
+        // penghalang di sebelah permen yang remuk terkena satu pukulan,
+        // cokelat di sebelahnya langsung hancur
+        for pos in remuk.iter() {
+            for tetangga in self.tetangga(*pos) {
+                match self.get_petak(tetangga) {
+                    Petak::Penghalang(_) => { penghalang_dipukul.tambah(tetangga); }
+                    Petak::Cokelat => { cokelat_hancur.tambah(tetangga); }
+                    _ => {}
+                }
+            }
+        }
+        for pos in kunci_dibuka.iter() {
+            self.set_kunci(*pos, false);
+        }
+        let mut kosong = remuk.clone();
+        for pos in cokelat_hancur.iter() {
+            self.set_petak(*pos, Petak::Biasa);
+            kosong.tambah(*pos);
+        }
+        let mut bahan_terkumpul = self.bahan_di_keluar();
+        kosong.tambah_vek(bahan_terkumpul.clone());
+        let mut penghalang_hancur = vec![];
+        for pos in penghalang_dipukul.iter() {
+            if let Petak::Penghalang(nyawa) = self.get_petak(*pos) {
+                if nyawa > 1 {
+                    self.set_petak(*pos, Petak::Penghalang(nyawa - 1));
+                } else {
+                    self.set_petak(*pos, Petak::Biasa);
+                    kosong.tambah(*pos);
+                    penghalang_hancur.push(*pos);
+                }
+            }
+        }
+
+        // setiap permen yang remuk membersihkan satu lapisan jeli di bawahnya
+        let mut jeli_dibersihkan = vec![];
+        for pos in remuk.iter() {
+            let lapisan = self.get_jeli(*pos);
+            if lapisan > 0 {
+                self.set_jeli(*pos, lapisan - 1);
+                jeli_dibersihkan.push((*pos, lapisan - 1));
+            }
+        }
+
+        // pelangi tidak punya warna, jadi dicatat terpisah
+        let (pelangi_remuk, remuk_warna): (Vec<Posisi>, Vec<Posisi>) =
+            remuk.iter().partition(|pos| self.get_jenis(**pos) == Jenis::Pelangi);
+        let remuk_warna = remuk_warna.into_iter().map(|pos| (pos, self.get_warna(pos))).collect();
+        for (pos, jenis) in spesial_dibuat.iter() {
+            self.set_jenis(*pos, jenis.clone());
+        }
+        let hilang: Vec<Posisi> = remuk.iter().chain(bahan_terkumpul.iter()).cloned().collect();
+        let (mut jatuh, mut muncul, mut fase) = self.jatuhkan(&hilang, kosong);
+
+        // bahan yang jatuh sampai ke petak keluar langsung terkumpul pada tingkat yang sama
+        loop {
+            let sampai = self.bahan_di_keluar();
+            if sampai.is_empty() { break }
+            let mut kosong = Dempet::new();
+            kosong.tambah_vek(sampai.clone());
+            let (jatuh_lagi, muncul_lagi, fase_lagi) = self.jatuhkan(&sampai, kosong);
+            jatuh.extend(jatuh_lagi);
+            muncul.extend(muncul_lagi);
+            fase.extend(fase_lagi);
+            bahan_terkumpul.extend(sampai);
+        }
+
+        Kaskade {
+            tingkat,
+            kelompok,
+            remuk: remuk_warna,
+            pelangi_remuk,
+            spesial_dibuat,
+            spesial_dipicu,
+            penghalang_dipukul: penghalang_dipukul.vektor,
+            penghalang_hancur,
+            cokelat_hancur: cokelat_hancur.vektor,
+            kunci_dibuka: kunci_dibuka.vektor,
+            bahan_terkumpul,
+            bom_dijinakkan,
+            jeli_dibersihkan,
+            jatuh,
+            muncul,
+            fase,
+        }
+    }
+
+    // Menghilangkan permen di posisi hilang lalu menjatuhkan permen ke semua posisi di kosong.
+    // Mengembalikan permen yang jatuh, permen baru yang muncul, dan tahap animasinya
+    fn jatuhkan(&self, hilang: &[Posisi], mut kosong: Dempet) -> Jatuhan {
+        kosong.urutkan();
+        let (jatuh, muncul) = self.rencana_jatuh(&kosong);
+        let gerak = |dari: Posisi, ke: Posisi| Gerak { id: self.get_id(dari), dari: (dari.0 as isize, dari.1), ke };
+        let fase_remuk = Fase::Remuk(hilang.iter().map(|pos| gerak(*pos, *pos)).collect());
+        let fase_jatuh = Fase::Jatuh(jatuh.iter().map(|(dari, ke)| gerak(*dari, *ke)).collect());
+        for pos in kosong.iter() {
+            self.remukan(*pos);
+        }
+        // permen baru berbaris di atas bagian kolomnya, sejauh jumlah permen baru di bagian itu
+        let mut masuk = vec![];
+        for bagian in muncul.chunk_by(|a, b| a.1 == b.1 && a.0 + 1 == b.0) {
+            masuk.extend(bagian.iter().map(|pos| Gerak {
+                id: self.get_id(*pos),
+                dari: (pos.0 as isize - bagian.len() as isize, pos.1),
+                ke: *pos,
+            }));
+        }
+        let fase_muncul = Fase::Muncul(masuk);
+        let muncul = muncul.into_iter().map(|pos| (pos, self.get_warna(pos))).collect();
+        (jatuh, muncul, vec![fase_remuk, fase_jatuh, fase_muncul])
+    }
+
+    // Permen yang akan jatuh (dari, ke) dan posisi permen baru jika semua posisi
+    // di kosong diremukan, dihitung per bagian kolom di antara petak yang tetap
+    fn rencana_jatuh(&self, kosong: &Dempet) -> (Vec<(Posisi, Posisi)>, Vec<Posisi>) {
+        let mut jatuh = vec![];
+        let mut muncul = vec![];
+        for x in 0..self.ukuran {
+            let mut bawah = self.ukuran;
+            while bawah > 0 {
+                // bagian kolom dari atas sampai bawah - 1 yang tidak dipisah petak tetap
+                if self.tetap((bawah - 1, x)) {
+                    bawah -= 1;
+                    continue;
+                }
+                let mut atas = bawah - 1;
+                while atas > 0 && !self.tetap((atas - 1, x)) {
+                    atas -= 1;
+                }
+
+                let mut tujuan = bawah;
+                for y in (atas..bawah).rev() {
+                    if kosong.contains(&(y, x)) { continue }
+                    tujuan -= 1;
+                    if y != tujuan {
+                        jatuh.push(((y, x), (tujuan, x)));
+                    }
+                }
+                muncul.extend((atas..tujuan).map(|y| (y, x)));
+                bawah = atas;
+            }
+        }
+        (jatuh, muncul)
+    }
+
+    /// Mengacak letak semua permen yang bisa ditukar sampai tidak ada
+    /// permen dempet dan ada permen yang bisa ditukar.
+    /// Mengembalikan false jika susunan seperti itu tidak ditemukan, letak permen tidak berubah
+    pub fn kocok(&self) -> bool {
+        let mut semua_posisi = vec![];
+        for y in 0..self.ukuran {
+            for x in 0..self.ukuran {
+                if self.bisa_ditukar((y, x)) {
+                    semua_posisi.push((y, x));
+                }
+            }
+        }
+
+        let mut semua_permen: Vec<Permen> =
+            semua_posisi.iter().map(|pos| self.isi[pos.0][pos.1].clone()).collect();
+        let asli = semua_permen.clone();
+        for _ in 0..100 {
+            semua_permen.shuffle(&mut *self.acak.borrow_mut());
+            for (pos, permen) in semua_posisi.iter().zip(semua_permen.iter()) {
+                self.isi[pos.0][pos.1].salin(permen);
+            }
+            if self.semua_dempet().is_empty() && !self.cek_kemungkinan().is_empty() {
+                return true;
+            }
+        }
+        for (pos, permen) in semua_posisi.iter().zip(asli.iter()) {
+            self.isi[pos.0][pos.1].salin(permen);
+        }
+        false
+    }
+}
+
+/// Sekelompok permen dempet berwarna sama yang diremukan bersamaan
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kelompok {
+    pub warna: Warna,
+    pub dempet: Dempet,
+}
+
+/// Semua kelompok yang diremukan pada satu tingkat kaskade.
+/// Tingkat 1 adalah remukan langsung dari penukaran permen.
+/// remuk berisi semua permen berwarna yang hilang, termasuk yang terkena permen spesial
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kaskade {
+    pub tingkat: u32,
+    pub kelompok: Vec<Kelompok>,
+    pub remuk: Vec<(Posisi, Warna)>,
+    /// Pelangi yang ikut hilang, tidak dihitung sebagai warna mana pun
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pelangi_remuk: Vec<Posisi>,
+    pub spesial_dibuat: Vec<(Posisi, Jenis)>,
+    pub spesial_dipicu: Vec<(Posisi, Jenis)>,
+    pub penghalang_dipukul: Vec<Posisi>,
+    /// Penghalang yang pukulan terakhirnya ada di tingkat ini, petaknya kini berisi permen
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub penghalang_hancur: Vec<Posisi>,
+    /// Cokelat yang hancur di tingkat ini, petaknya kini berisi permen
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cokelat_hancur: Vec<Posisi>,
+    /// Permen terkunci yang kandangnya terbuka, permennya tetap di tempat
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kunci_dibuka: Vec<Posisi>,
+    /// Bahan yang sampai di petak keluar dan diambil dari papan
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bahan_terkumpul: Vec<Posisi>,
+    /// Bom yang ikut remuk sebelum sempat meledak
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bom_dijinakkan: Vec<Posisi>,
+    /// Petak yang kehilangan satu lapisan jeli, beserta sisa lapisannya
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub jeli_dibersihkan: Vec<(Posisi, u8)>,
+    /// Permen yang jatuh setelah permen di bawahnya remuk, (dari, ke)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub jatuh: Vec<(Posisi, Posisi)>,
+    /// Permen baru yang muncul untuk mengisi papan, di posisi akhirnya
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub muncul: Vec<(Posisi, Warna)>,
+    /// Tahap animasi tingkat ini: remuk, jatuh, lalu muncul
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fase: Vec<Fase>,
+}
+
+/// Perpindahan satu permen dalam satu tahap animasi
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gerak {
+    pub id: u32,
+    /// Letak awal permen, barisnya negatif untuk permen baru yang masuk dari atas papan
+    pub dari: (isize, usize),
+    pub ke: Posisi,
+}
+
+/// Tahap animasi satu giliran. Setiap tahap selesai dimainkan sebelum tahap berikutnya
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fase {
+    /// Dua permen bertukar tempat
+    Tukar(Vec<Gerak>),
+    /// Permen hilang dari tempatnya (dari dan ke sama)
+    Remuk(Vec<Gerak>),
+    /// Permen jatuh mengisi tempat yang kosong
+    Jatuh(Vec<Gerak>),
+    /// Permen baru masuk dari atas bagian kolomnya ke tempatnya
+    Muncul(Vec<Gerak>),
+}
+
+impl Fase {
+    /// Semua perpindahan permen pada tahap ini
+    pub fn gerak(&self) -> &[Gerak] {
+        match self {
+            Fase::Tukar(gerak) | Fase::Remuk(gerak) | Fase::Jatuh(gerak) | Fase::Muncul(gerak) => gerak,
+        }
+    }
+}
+
+impl Kaskade {
+    /// Jumlah permen yang diremukan pada tingkat ini
+    pub fn jumlah_permen(&self) -> usize {
+        self.remuk.len() + self.pelangi_remuk.len()
+    }
+
+    /// Skor untuk tingkat ini, makin dalam kaskadenya makin besar pengalinya
+    pub fn skor(&self) -> u32 {
+        self.jumlah_permen() as u32 * SKOR_PER_PERMEN * self.tingkat
+    }
+}
+
+const SKOR_PER_PERMEN: u32 = 10;
+const DEMPET_BESAR: usize = 4;
+const BONUS_DEMPET_BESAR_MS: u64 = 1_000;
+const BONUS_KASKADE_MS: u64 = 500;
+
+/// Kesalahan yang bisa terjadi saat menjalankan aksi pada Sesi
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Galat {
+    DiLuarPapan(Posisi),
+    TidakBersebelahan(Posisi, Posisi),
+    TidakBisaDitukar(Posisi),
+    TidakAdaDempet,
+    PermainanSelesai,
+    BukanPermen(Posisi),
+    PenguatHabis(Penguat),
+    TanpaBatasLangkah,
+    RiwayatMati,
+    RiwayatKosong,
+    FormatTidakSah(String),
+    LevelTidakAda(usize),
+    LevelTerkunci(usize),
+    GagalDikocok,
+}
+
+impl Display for Galat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Galat::DiLuarPapan(pos) => write!(f, "posisi {}:{} di luar papan", pos.0, pos.1),
+            Galat::TidakBersebelahan(pos1, pos2) => write!(
+                f, "permen {}:{} dan {}:{} tidak bersebelahan", pos1.0, pos1.1, pos2.0, pos2.1
+            ),
+            Galat::TidakBisaDitukar(pos) => write!(f, "permen {}:{} tidak bisa ditukar", pos.0, pos.1),
+            Galat::TidakAdaDempet => write!(f, "penukaran tidak menghasilkan permen dempet"),
+            Galat::PermainanSelesai => write!(f, "permainan sudah selesai"),
+            Galat::BukanPermen(pos) => write!(f, "tidak ada permen di {}:{}", pos.0, pos.1),
+            Galat::PenguatHabis(penguat) => write!(f, "persediaan {:?} sudah habis", penguat),
+            Galat::TanpaBatasLangkah => write!(f, "permainan ini tidak punya batas langkah"),
+            Galat::RiwayatMati => write!(f, "riwayat dimatikan untuk permainan ini"),
+            Galat::RiwayatKosong => write!(f, "tidak ada giliran untuk diurungkan atau diulangi"),
+            Galat::FormatTidakSah(teks) => write!(f, "format tidak sah: {}", teks),
+            Galat::LevelTidakAda(indeks) => write!(f, "level {} tidak ada", indeks),
+            Galat::LevelTerkunci(indeks) => write!(f, "level {} masih terkunci", indeks),
+            Galat::GagalDikocok => write!(f, "tidak ada susunan papan yang bisa dimainkan"),
+        }
+    }
+}
+
+impl std::error::Error for Galat {}
+
+/// Hasil dari satu giliran yang berhasil
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HasilGiliran {
+    pub kaskade: Vec<Kaskade>,
+    pub skor: u32,
+    pub bonus_waktu_ms: u64,
+    pub penghalang_muncul: Option<Posisi>,
+    /// Cokelat yang menyebar di akhir giliran, (cokelat asal, permen yang ditelan)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cokelat_menyebar: Option<(Posisi, Posisi)>,
+    /// Bom yang sisa gilirannya habis di akhir giliran
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bom_meledak: Vec<Posisi>,
+    /// Tahap animasi pertukaran, hanya ada jika giliran diawali menukar permen
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tukar: Option<Fase>,
+}
+
+impl HasilGiliran {
+    /// Semua tahap animasi giliran ini secara berurutan:
+    /// tukar, lalu remuk, jatuh, dan muncul untuk setiap tingkat kaskade
+    pub fn fase(&self) -> Vec<Fase> {
+        self.tukar.iter().cloned().chain(self.kaskade.iter().flat_map(|ksk| ksk.fase.clone())).collect()
+    }
+}
+
+/// Kejadian selama permainan untuk tampilan, suara, dan analitik.
+/// Setiap giliran menghasilkan peristiwa berurutan sesuai kejadiannya
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Peristiwa {
+    TukarDicoba(Posisi, Posisi),
+    TukarDitolak(Posisi, Posisi, Galat),
+    DempetDitemukan(Kelompok),
+    SpesialDibuat(Posisi, Jenis),
+    SpesialDipicu(Posisi, Jenis),
+    /// Permen jatuh dari posisi pertama ke posisi kedua
+    PermenJatuh(Posisi, Posisi),
+    PermenMuncul(Posisi, Warna),
+    /// Satu lapisan jeli dibersihkan, beserta sisa lapisannya
+    JeliDibersihkan(Posisi, u8),
+    /// Penghalang terkena satu pukulan
+    PenghalangDipukul(Posisi),
+    /// Penghalang hancur setelah pukulan terakhirnya
+    PenghalangHancur(Posisi),
+    CokelatHancur(Posisi),
+    /// Kandang permen terbuka, permennya tidak ikut remuk
+    KunciDibuka(Posisi),
+    BahanTerkumpul(Posisi),
+    /// Bom ikut remuk sebelum sisa gilirannya habis
+    BomDijinakkan(Posisi),
+    /// Cokelat di posisi pertama menelan permen di posisi kedua
+    CokelatMenyebar(Posisi, Posisi),
+    /// Sisa giliran bom habis, permainan kalah
+    BomMeledak(Posisi),
+    /// Awal satu tingkat kaskade, dimulai dari 1
+    TingkatKaskade(u32),
+    SkorBertambah(u32),
+    KemajuanSasaran(Vec<Kemajuan>),
+    Dikocok,
+    PermainanSelesai(Ringkasan),
+}
+
+/// Aturan satu tingkat kesulitan pada mode tanpa akhir
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kesulitan {
+    pub skor_minimal: u32,
+    /// Bobot setiap warna untuk permen baru, berurutan mulai dari Merah
+    pub bobot_warna: &'static [u32],
+    /// Peluang (persen) munculnya penghalang baru setiap giliran
+    pub peluang_penghalang: u32,
+}
+
+/// Tingkat kesulitan mode tanpa akhir. Makin tinggi skornya, makin banyak
+/// ragam warnanya, makin rata bobotnya, dan penghalang mulai bermunculan
+pub const TINGKAT_KESULITAN: [Kesulitan; 5] = [
+    Kesulitan { skor_minimal: 0, bobot_warna: &[4, 3, 3, 2], peluang_penghalang: 0 },
+    Kesulitan { skor_minimal: 1_000, bobot_warna: &[3, 3, 2, 2, 2], peluang_penghalang: 0 },
+    Kesulitan { skor_minimal: 3_000, bobot_warna: &[2, 2, 2, 2, 2, 1], peluang_penghalang: 5 },
+    Kesulitan { skor_minimal: 6_000, bobot_warna: &[1, 1, 1, 1, 1, 1, 1], peluang_penghalang: 10 },
+    Kesulitan { skor_minimal: 10_000, bobot_warna: &[1, 1, 1, 1, 1, 1, 1], peluang_penghalang: 20 },
+];
+
+impl Kesulitan {
+    /// Indeks tingkat kesulitan untuk sebuah skor
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::Kesulitan;
+    /// assert_eq!(Kesulitan::tingkat(0), 0);
+    /// assert_eq!(Kesulitan::tingkat(3_500), 2);
+    /// ```
+    pub fn tingkat(skor: u32) -> usize {
+        TINGKAT_KESULITAN.iter().rposition(|ksl| skor >= ksl.skor_minimal).unwrap_or(0)
+    }
+
+    /// Palet warna untuk tingkat kesulitan ini
+    pub fn palet(&self) -> Vec<(Warna, u32)> {
+        SEMUA_WARNA.iter().cloned().zip(self.bobot_warna.iter().cloned()).collect()
+    }
+}
+
+const TAMBAHAN_LANGKAH: u32 = 5;
+
+/// Jenis penguat yang bisa dipakai pemain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Penguat {
+    /// Meremukan satu permen pilihan
+    Palu,
+    /// Menukar dua permen bersebelahan tanpa harus dempet
+    TukarBebas,
+    /// Mengacak ulang papan
+    Kocok,
+    /// Menambah 5 langkah
+    TambahLangkah,
+}
+
+/// Jumlah setiap penguat yang dimiliki pemain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Persediaan {
+    pub palu: u32,
+    pub tukar_bebas: u32,
+    pub kocok: u32,
+    pub tambah_langkah: u32,
+}
+
+impl Persediaan {
+    fn isian(&mut self, penguat: Penguat) -> &mut u32 {
+        match penguat {
+            Penguat::Palu => &mut self.palu,
+            Penguat::TukarBebas => &mut self.tukar_bebas,
+            Penguat::Kocok => &mut self.kocok,
+            Penguat::TambahLangkah => &mut self.tambah_langkah,
+        }
+    }
+
+    /// Jumlah persediaan sebuah penguat
+    pub fn jumlah(&self, penguat: Penguat) -> u32 {
+        match penguat {
+            Penguat::Palu => self.palu,
+            Penguat::TukarBebas => self.tukar_bebas,
+            Penguat::Kocok => self.kocok,
+            Penguat::TambahLangkah => self.tambah_langkah,
+        }
+    }
+
+    /// Menambah persediaan sebuah penguat
+    pub fn tambah(&mut self, penguat: Penguat, jumlah: u32) {
+        *self.isian(penguat) += jumlah;
+    }
+
+    /// Mengambil satu penguat dari persediaan
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Galat, Penguat, Persediaan};
+    /// let mut persediaan = Persediaan::default();
+    /// persediaan.tambah(Penguat::Palu, 1);
+    /// assert_eq!(persediaan.ambil(Penguat::Palu), Ok(()));
+    /// assert_eq!(persediaan.ambil(Penguat::Palu), Err(Galat::PenguatHabis(Penguat::Palu)));
+    /// ```
+    pub fn ambil(&mut self, penguat: Penguat) -> Result<(), Galat> {
+        let jumlah = self.isian(penguat);
+        if *jumlah == 0 {
+            return Err(Galat::PenguatHabis(penguat));
+        }
+        *jumlah -= 1;
+        Ok(())
+    }
+}
+
+/// Keadaan sesi yang disimpan di riwayat
+#[derive(Debug, Clone)]
+pub struct Cuplikan {
+    papan: Papan,
+    skor: u32,
+    langkah: u32,
+    batas_langkah: Option<u32>,
+    statistik: Statistik,
+    kesulitan: Option<usize>,
+    persediaan: Persediaan,
+    bom_meledak: Vec<Posisi>,
+}
+
+/// Riwayat giliran dengan jumlah yang dibatasi
+#[derive(Debug, Clone)]
+pub struct Riwayat {
+    pub batas: usize,
+    mundur: VecDeque<Cuplikan>,
+    maju: Vec<Cuplikan>,
+}
+
+impl Riwayat {
+    pub fn new(batas: usize) -> Riwayat {
+        Riwayat { batas, mundur: VecDeque::new(), maju: vec![] }
+    }
+
+    /// Jumlah giliran yang bisa diurungkan
+    pub fn bisa_diurungkan(&self) -> usize {
+        self.mundur.len()
+    }
+
+    /// Jumlah giliran yang bisa diulangi
+    pub fn bisa_diulangi(&self) -> usize {
+        self.maju.len()
+    }
+
+    // Mencatat giliran baru, giliran yang sudah diurungkan tidak bisa diulangi lagi
+    fn catat(&mut self, cuplikan: Cuplikan) {
+        self.maju.clear();
+        self.simpan(cuplikan);
+    }
+
+    fn simpan(&mut self, cuplikan: Cuplikan) {
+        if self.batas == 0 { return }
+        if self.mundur.len() == self.batas {
+            self.mundur.pop_front();
+        }
+        self.mundur.push_back(cuplikan);
+    }
+
+    fn mundur(&mut self) -> Result<Cuplikan, Galat> {
+        self.mundur.pop_back().ok_or(Galat::RiwayatKosong)
+    }
+
+    fn maju(&mut self) -> Result<Cuplikan, Galat> {
+        self.maju.pop().ok_or(Galat::RiwayatKosong)
+    }
+}
+
+/// Hitungan hasil permainan selama satu sesi
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statistik {
+    pub remuk_warna: [u32; 7],
+    pub jeli_dibersihkan: u32,
+    pub bahan_terkumpul: u32,
+    pub kaskade_terbesar: u32,
+    pub spesial_dibuat: u32,
+}
+
+impl Statistik {
+    /// Mencatat semua permen yang diremukan pada kaskade
+    pub fn catat(&mut self, kaskade: &[Kaskade]) {
+        for ksk in kaskade {
+            for (_, warna) in ksk.remuk.iter() {
+                self.remuk_warna[warna.indeks()] += 1;
+            }
+            self.spesial_dibuat += ksk.spesial_dibuat.len() as u32;
+            self.jeli_dibersihkan += ksk.jeli_dibersihkan.len() as u32;
+            self.bahan_terkumpul += ksk.bahan_terkumpul.len() as u32;
+        }
+        self.kaskade_terbesar = self.kaskade_terbesar.max(kaskade.len() as u32);
+    }
+}
+
+/// Sasaran sebuah level. Sasaran dasar bisa digabung dengan
+/// Dan (semua harus tercapai) atau Atau (salah satu tercapai)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sasaran {
+    Skor(u32),
+    RemukanWarna(Warna, u32),
+    BersihkanJeli(u32),
+    TurunkanBahan(u32),
+    Dan(Vec<Sasaran>),
+    Atau(Vec<Sasaran>),
+}
+
+impl Display for Sasaran {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sasaran::Skor(_) => write!(f, "skor"),
+            Sasaran::RemukanWarna(warna, _) => write!(f, "{}", warna),
+            Sasaran::BersihkanJeli(_) => write!(f, "jeli"),
+            Sasaran::TurunkanBahan(_) => write!(f, "bahan"),
+            Sasaran::Dan(_) => write!(f, "dan"),
+            Sasaran::Atau(_) => write!(f, "atau"),
+        }
+    }
+}
+
+/// Kemajuan dari satu sasaran dasar
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Kemajuan {
+    pub sasaran: Sasaran,
+    pub nilai: u32,
+    pub target: u32,
+}
+
+impl Kemajuan {
+    pub fn tercapai(&self) -> bool {
+        self.nilai >= self.target
+    }
+}
+
+impl Sasaran {
+    /// Kemajuan setiap sasaran dasar, berurutan seperti yang ditulis
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Sasaran, Statistik, Warna};
+    /// let sasaran = Sasaran::Dan(vec![Sasaran::Skor(1000), Sasaran::RemukanWarna(Warna::Merah, 20)]);
+    /// let kemajuan = sasaran.kemajuan(300, &Statistik::default());
+    /// assert_eq!(kemajuan.len(), 2);
+    /// assert_eq!(kemajuan[0].nilai, 300);
+    /// ```
+    pub fn kemajuan(&self, skor: u32, statistik: &Statistik) -> Vec<Kemajuan> {
+        let dasar = |nilai: u32, target: u32| {
+            vec![Kemajuan { sasaran: self.clone(), nilai: nilai.min(target), target }]
+        };
+        match self {
+            Sasaran::Skor(target) => dasar(skor, *target),
+            Sasaran::RemukanWarna(warna, target) => dasar(statistik.remuk_warna[warna.indeks()], *target),
+            Sasaran::BersihkanJeli(target) => dasar(statistik.jeli_dibersihkan, *target),
+            Sasaran::TurunkanBahan(target) => dasar(statistik.bahan_terkumpul, *target),
+            Sasaran::Dan(semua) | Sasaran::Atau(semua) => semua
+                .iter()
+                .flat_map(|sasaran| sasaran.kemajuan(skor, statistik))
+                .collect(),
+        }
+    }
+
+    /// Apakah sasaran sudah tercapai
+    pub fn tercapai(&self, skor: u32, statistik: &Statistik) -> bool {
+        match self {
+            Sasaran::Dan(semua) => semua.iter().all(|sasaran| sasaran.tercapai(skor, statistik)),
+            Sasaran::Atau(semua) => semua.iter().any(|sasaran| sasaran.tercapai(skor, statistik)),
+            _ => self.kemajuan(skor, statistik).iter().all(|kmj| kmj.tercapai()),
+        }
+    }
+}
+
+/// Ringkasan akhir sebuah level.
+/// Bentuk string-nya "skor:1200 bintang:2 langkah:14 kaskade:3 spesial:2 sasaran:1"
+/// bisa disimpan lalu dibaca kembali dengan parse
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ringkasan {
+    pub skor: u32,
+    pub bintang: u8,
+    pub langkah: u32,
+    pub kaskade_terbesar: u32,
+    pub spesial_dibuat: u32,
+    pub sasaran_tercapai: bool,
+}
+
+impl Ringkasan {
+    /// Apakah level dianggap lulus, yaitu mendapat bintang atau sasarannya tercapai
+    /// sehingga level tanpa ambang bintang tetap bisa lulus
+    pub fn lulus(&self) -> bool {
+        self.bintang > 0 || self.sasaran_tercapai
+    }
+
+    /// Apakah ringkasan ini lebih baik dari ringkasan lain: lulus mengalahkan tidak lulus,
+    /// lalu bintang lebih banyak atau bintang sama dengan skor lebih tinggi
+    pub fn lebih_baik(&self, lain: &Ringkasan) -> bool {
+        (self.lulus(), self.bintang, self.skor) > (lain.lulus(), lain.bintang, lain.skor)
+    }
+}
+
+impl Display for Ringkasan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "skor:{} bintang:{} langkah:{} kaskade:{} spesial:{} sasaran:{}",
+            self.skor,
+            self.bintang,
+            self.langkah,
+            self.kaskade_terbesar,
+            self.spesial_dibuat,
+            self.sasaran_tercapai as u8,
+        )
+    }
+}
+
+impl FromStr for Ringkasan {
+    type Err = Galat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ringkasan = Ringkasan::default();
+        for bagian in s.split_whitespace() {
+            let galat = || Galat::FormatTidakSah(bagian.to_string());
+            let (kunci, nilai) = bagian.split_once(':').ok_or_else(galat)?;
+            let nilai: u32 = nilai.parse().map_err(|_| galat())?;
+            match kunci {
+                "skor" => ringkasan.skor = nilai,
+                "bintang" if nilai <= 3 => ringkasan.bintang = nilai as u8,
+                "langkah" => ringkasan.langkah = nilai,
+                "kaskade" => ringkasan.kaskade_terbesar = nilai,
+                "spesial" => ringkasan.spesial_dibuat = nilai,
+                "sasaran" if nilai <= 1 => ringkasan.sasaran_tercapai = nilai == 1,
+                _ => return Err(galat()),
+            }
+        }
+        Ok(ringkasan)
+    }
+}
+
+/// Jadwal munculnya bahan dari atas papan pada level menurunkan bahan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JadwalBahan {
+    /// Satu bahan muncul pada isian permen baru setiap sekian langkah
+    pub setiap: u32,
+    /// Jumlah bahan paling banyak di papan sekaligus
+    pub maks: u32,
+}
+
+/// Jadwal munculnya bom dari atas papan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JadwalBom {
+    /// Satu bom muncul pada isian permen baru setiap sekian langkah
+    pub setiap: u32,
+    /// Sisa giliran bom ketika muncul
+    pub hitungan: u8,
+    /// Jumlah bom paling banyak di papan sekaligus
+    pub maks: u32,
+}
+
+/// Satu sesi permainan di atas sebuah Papan.
+/// Sesi menyimpan skor, jumlah langkah, sisa waktu (untuk mode berwaktu),
+/// dan sasaran level beserta statistik untuk menghitung kemajuannya
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sesi {
+    pub papan: Papan,
+    pub skor: u32,
+    pub langkah: u32,
+    pub batas_langkah: Option<u32>,
+    pub sisa_waktu_ms: Option<u64>,
+    pub sasaran: Option<Sasaran>,
+    pub statistik: Statistik,
+    /// Skor minimal untuk 1, 2, dan 3 bintang, sesi tanpa ambang tidak memberi bintang
+    pub ambang_bintang: Option<[u32; 3]>,
+    /// Tingkat kesulitan saat ini, hanya ada pada mode tanpa akhir
+    pub kesulitan: Option<usize>,
+    pub persediaan: Persediaan,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub jadwal_bahan: Option<JadwalBahan>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub jadwal_bom: Option<JadwalBom>,
+    /// Bom yang sudah meledak, permainan kalah jika tidak kosong
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bom_meledak: Vec<Posisi>,
+    /// Riwayat untuk urungkan dan ulangi, None berarti dimatikan
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub riwayat: Option<Riwayat>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) peristiwa: Vec<Peristiwa>,
+}
+
+impl Sesi {
+    pub fn new(papan: Papan) -> Sesi {
+        Sesi {
+            papan,
+            skor: 0,
+            langkah: 0,
+            batas_langkah: None,
+            sisa_waktu_ms: None,
+            sasaran: None,
+            statistik: Statistik::default(),
+            ambang_bintang: None,
+            kesulitan: None,
+            persediaan: Persediaan::default(),
+            jadwal_bahan: None,
+            jadwal_bom: None,
+            bom_meledak: vec![],
+            riwayat: None,
+            peristiwa: vec![],
+        }
+    }
+
+    /// Membuat sesi tanpa akhir. Papan diisi ulang dengan palet tingkat
+    /// kesulitan pertama dan permainan baru selesai ketika tidak ada lagi
+    /// permen yang bisa ditukar
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Sesi};
+    /// let sesi = Sesi::tanpa_akhir(Papan::new(7, 4));
+    /// assert_eq!(sesi.kesulitan, Some(0));
+    /// assert_eq!(sesi.papan.ragam_warna, 4);
+    /// ```
+    pub fn tanpa_akhir(papan: Papan) -> Sesi {
+        let mut sesi = Sesi::new(papan);
+        sesi.terapkan_kesulitan();
+
+        let mut semua_posisi = vec![];
+        for y in 0..sesi.papan.ukuran {
+            for x in 0..sesi.papan.ukuran {
+                if sesi.papan.punya_permen((y, x)) {
+                    semua_posisi.push((y, x));
+                }
+            }
+        }
+        // acak_isian sudah mengocok papan jika masih dempet, ulangi dengan warna baru sampai bersih
+        for _ in 0..10 {
+            if sesi.papan.acak_isian(&semua_posisi) { break }
+        }
+        sesi
+    }
+
+    /// Membatasi jumlah langkah, permainan selesai ketika langkahnya habis
+    pub fn dengan_batas_langkah(self, batas_langkah: u32) -> Sesi {
+        Sesi { batas_langkah: Some(batas_langkah), ..self }
+    }
+
+    /// Menentukan sasaran level untuk sesi ini
+    pub fn dengan_sasaran(self, sasaran: Sasaran) -> Sesi {
+        Sesi { sasaran: Some(sasaran), ..self }
+    }
+
+    /// Menentukan skor minimal untuk mendapat 1, 2, dan 3 bintang
+    pub fn dengan_ambang_bintang(self, ambang_bintang: [u32; 3]) -> Sesi {
+        Sesi { ambang_bintang: Some(ambang_bintang), ..self }
+    }
+
+    /// Jumlah bintang dari skor saat ini.
+    /// Jika sesi tidak punya ambang bintang, punya sasaran yang belum tercapai,
+    /// atau ada bom yang meledak, tidak ada bintang
+    pub fn bintang(&self) -> u8 {
+        let Some(ambang_bintang) = self.ambang_bintang else { return 0 };
+        if (self.sasaran.is_some() && !self.sasaran_tercapai()) || !self.bom_meledak.is_empty() {
+            return 0;
+        }
+        ambang_bintang.iter().filter(|ambang| self.skor >= **ambang).count() as u8
+    }
+
+    /// Ringkasan hasil permainan sesi ini
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Sesi};
+    /// let sesi = Sesi::new(Papan::new(7, 3)).dengan_ambang_bintang([100, 200, 300]);
+    /// let ringkasan = sesi.ringkasan();
+    /// assert_eq!(ringkasan.bintang, 0);
+    /// assert_eq!(ringkasan.to_string().parse(), Ok(ringkasan));
+    /// ```
+    pub fn ringkasan(&self) -> Ringkasan {
+        Ringkasan {
+            skor: self.skor,
+            bintang: self.bintang(),
+            langkah: self.langkah,
+            kaskade_terbesar: self.statistik.kaskade_terbesar,
+            spesial_dibuat: self.statistik.spesial_dibuat,
+            sasaran_tercapai: self.sasaran_tercapai(),
+        }
+    }
+
+    /// Membuat sesi berwaktu, permainan selesai ketika waktunya habis
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Sesi};
+    /// let mut sesi = Sesi::berwaktu(Papan::new(7, 3), 60_000);
+    /// sesi.tick(60_000);
+    /// assert!(sesi.selesai());
+    /// ```
+    pub fn berwaktu(papan: Papan, durasi_ms: u64) -> Sesi {
+        Sesi { sisa_waktu_ms: Some(durasi_ms), ..Sesi::new(papan) }
+    }
+
+    /// Memajukan jam permainan sebanyak delta_ms.
+    /// Waktu diberikan dari luar agar sesi tidak bergantung pada jam dinding.
+    /// Peristiwa PermainanSelesai dicatat sekali ketika waktunya habis
+    pub fn tick(&mut self, delta_ms: u64) {
+        let Some(sisa) = self.sisa_waktu_ms else { return };
+        let habis = sisa > 0 && delta_ms >= sisa && !self.selesai();
+        self.sisa_waktu_ms = Some(sisa.saturating_sub(delta_ms));
+        if habis {
+            self.peristiwa.push(Peristiwa::PermainanSelesai(self.ringkasan()));
+        }
+    }
+
+    /// Apakah permainan sudah selesai, baik karena waktu atau langkah habis,
+    /// karena bom meledak, karena papan buntu, maupun karena sasaran sudah tercapai
+    pub fn selesai(&self) -> bool {
+        self.buntu() || self.berakhir()
+    }
+
+    /// Apakah papan buntu, tidak ada lagi permen yang bisa ditukar. Berlaku untuk
+    /// semua mode, papan yang buntu masih bisa dikocok dengan penguat
+    pub fn buntu(&self) -> bool {
+        self.papan.cek_kemungkinan().is_empty()
+    }
+
+    // Apakah permainan selesai karena alasan selain papan yang buntu
+    fn berakhir(&self) -> bool {
+        self.sisa_waktu_ms == Some(0)
+            || self.sisa_langkah() == Some(0)
+            || !self.bom_meledak.is_empty()
+            || self.sasaran_tercapai()
+    }
+
+    /// Sisa langkah untuk sesi yang langkahnya dibatasi
+    pub fn sisa_langkah(&self) -> Option<u32> {
+        self.batas_langkah.map(|batas| batas.saturating_sub(self.langkah))
+    }
+
+    /// Apakah sasaran level sudah tercapai. Sesi tanpa sasaran tidak pernah tercapai
+    pub fn sasaran_tercapai(&self) -> bool {
+        match &self.sasaran {
+            Some(sasaran) => sasaran.tercapai(self.skor, &self.statistik),
+            None => false,
+        }
+    }
+
+    /// Kemajuan setiap sasaran dasar
+    pub fn kemajuan(&self) -> Vec<Kemajuan> {
+        match &self.sasaran {
+            Some(sasaran) => sasaran.kemajuan(self.skor, &self.statistik),
+            None => vec![],
+        }
+    }
+
+    /// Kemajuan sasaran dalam bentuk string "sasaran:nilai/target "
+    /// untuk ditampilkan oleh frontend
+    pub fn kemajuan_str(&self) -> String {
+        let mut result = String::new();
+        for kmj in self.kemajuan() {
+            result.push_str(&format!("{}:{}/{} ", kmj.sasaran, kmj.nilai, kmj.target))
+        }
+        result
+    }
+
+    /// Menukar dua permen yang bersebelahan lalu meremukan semua permen dempet.
+    /// Jika penukaran tidak menghasilkan permen dempet, permen dikembalikan
+    /// ke posisi semula
+    pub fn tukar(&mut self, pos1: Posisi, pos2: Posisi) -> Result<HasilGiliran, Galat> {
+        self.peristiwa.push(Peristiwa::TukarDicoba(pos1, pos2));
+        let hasil = self.rekam(|sesi| {
+            if sesi.selesai() { return Err(Galat::PermainanSelesai) }
+            sesi.cek_penukaran(pos1, pos2)?;
+
+            sesi.papan.tukar(pos1, pos2);
+            if sesi.papan.cek_dempet(pos1, 3).is_empty() && sesi.papan.cek_dempet(pos2, 3).is_empty() {
+                sesi.papan.tukar(pos1, pos2);
+                return Err(Galat::TidakAdaDempet);
+            }
+
+            let tukar = sesi.fase_tukar(pos1, pos2);
+            let kaskade = sesi.papan.remukan_kaskade(&[pos1, pos2]);
+            Ok(HasilGiliran { tukar: Some(tukar), ..sesi.akhiri_giliran(kaskade, true) })
+        });
+        if let Err(galat) = &hasil {
+            self.peristiwa.push(Peristiwa::TukarDitolak(pos1, pos2, galat.clone()));
+        }
+        hasil
+    }
+
+    // Menjalankan aksi dan mencatat keadaan sebelumnya ke riwayat jika aksi berhasil
+    fn rekam(
+        &mut self,
+        aksi: impl FnOnce(&mut Sesi) -> Result<HasilGiliran, Galat>,
+    ) -> Result<HasilGiliran, Galat> {
+        let cuplikan = self.riwayat.is_some().then(|| self.cuplikan());
+        let hasil = aksi(self)?;
+        if let (Some(riwayat), Some(cuplikan)) = (self.riwayat.as_mut(), cuplikan) {
+            riwayat.catat(cuplikan);
+        }
+        Ok(hasil)
+    }
+
+    fn cuplikan(&self) -> Cuplikan {
+        Cuplikan {
+            papan: self.papan.clone(),
+            skor: self.skor,
+            langkah: self.langkah,
+            batas_langkah: self.batas_langkah,
+            statistik: self.statistik.clone(),
+            kesulitan: self.kesulitan,
+            persediaan: self.persediaan.clone(),
+            bom_meledak: self.bom_meledak.clone(),
+        }
+    }
+
+    // Mengganti keadaan sesi dengan cuplikan, mengembalikan keadaan yang diganti
+    fn pulihkan(&mut self, cuplikan: Cuplikan) -> Cuplikan {
+        let sekarang = self.cuplikan();
+        self.papan = cuplikan.papan;
+        self.skor = cuplikan.skor;
+        self.langkah = cuplikan.langkah;
+        self.batas_langkah = cuplikan.batas_langkah;
+        self.statistik = cuplikan.statistik;
+        self.kesulitan = cuplikan.kesulitan;
+        self.persediaan = cuplikan.persediaan;
+        self.bom_meledak = cuplikan.bom_meledak;
+        sekarang
+    }
+
+    /// Mengaktifkan riwayat untuk urungkan dan ulangi,
+    /// paling banyak batas giliran terakhir yang disimpan
+    pub fn dengan_riwayat(self, batas: usize) -> Sesi {
+        Sesi { riwayat: Some(Riwayat::new(batas)), ..self }
+    }
+
+    /// Membatalkan giliran terakhir. Sisa waktu tidak ikut dikembalikan
+    pub fn urungkan(&mut self) -> Result<(), Galat> {
+        let cuplikan = self.riwayat.as_mut().ok_or(Galat::RiwayatMati)?.mundur()?;
+        let sekarang = self.pulihkan(cuplikan);
+        if let Some(riwayat) = self.riwayat.as_mut() {
+            riwayat.maju.push(sekarang);
+        }
+        Ok(())
+    }
+
+    /// Mengulangi giliran yang terakhir dibatalkan
+    pub fn ulangi(&mut self) -> Result<(), Galat> {
+        let cuplikan = self.riwayat.as_mut().ok_or(Galat::RiwayatMati)?.maju()?;
+        let sekarang = self.pulihkan(cuplikan);
+        if let Some(riwayat) = self.riwayat.as_mut() {
+            riwayat.simpan(sekarang);
+        }
+        Ok(())
+    }
+
+    // Tahap animasi untuk dua permen yang baru saja ditukar
+    fn fase_tukar(&self, pos1: Posisi, pos2: Posisi) -> Fase {
+        Fase::Tukar(vec![
+            Gerak { id: self.papan.get_id(pos2), dari: (pos1.0 as isize, pos1.1), ke: pos2 },
+            Gerak { id: self.papan.get_id(pos1), dari: (pos2.0 as isize, pos2.1), ke: pos1 },
+        ])
+    }
+
+    // Memastikan kedua posisi ada di papan, bersebelahan, dan bisa ditukar
+    fn cek_penukaran(&self, pos1: Posisi, pos2: Posisi) -> Result<(), Galat> {
+        for pos in [pos1, pos2] {
+            if pos.0 >= self.papan.ukuran || pos.1 >= self.papan.ukuran {
+                return Err(Galat::DiLuarPapan(pos));
+            }
+        }
+        if !bersebelahan(pos1, pos2) {
+            return Err(Galat::TidakBersebelahan(pos1, pos2));
+        }
+        for pos in [pos1, pos2] {
+            if !self.papan.bisa_ditukar(pos) {
+                return Err(Galat::TidakBisaDitukar(pos));
+            }
+        }
+        Ok(())
+    }
+
+    // Menghitung skor, statistik, dan bonus waktu dari kaskade.
+    // Hanya langkah biasa yang menambah jumlah langkah, memunculkan penghalang,
+    // menjadwalkan bahan dan bom, menyebarkan cokelat jika tidak ada cokelat yang hancur selama giliran,
+    // dan menghitung mundur bom selama sasaran belum tercapai
+    fn akhiri_giliran(&mut self, kaskade: Vec<Kaskade>, langkah: bool) -> HasilGiliran {
+        let skor = kaskade.iter().map(|ksk| ksk.skor()).sum();
+        let bonus_waktu_ms = match self.sisa_waktu_ms {
+            Some(_) => Sesi::bonus_waktu(&kaskade),
+            None => 0,
+        };
+
+        self.skor += skor;
+        self.statistik.catat(&kaskade);
+        if let Some(sisa) = self.sisa_waktu_ms.as_mut() {
+            *sisa += bonus_waktu_ms;
+        }
+        if langkah {
+            self.langkah += 1;
+        }
+
+        let mut penghalang_muncul = None;
+        if let Some(tingkat) = self.kesulitan {
+            let peluang = TINGKAT_KESULITAN[tingkat].peluang_penghalang;
+            if langkah && self.papan.acak.borrow_mut().gen_range(0..100) < peluang {
+                penghalang_muncul = self.papan.munculkan_penghalang(1);
+            }
+            self.terapkan_kesulitan();
+        }
+
+        if let Some(jadwal) = self.jadwal_bahan {
+            let tertunda = self.papan.bahan_tertunda.get();
+            let giliran_bahan = jadwal.setiap > 0 && self.langkah.is_multiple_of(jadwal.setiap);
+            if langkah && giliran_bahan && self.papan.jumlah_bahan() + tertunda < jadwal.maks {
+                self.papan.bahan_tertunda.set(tertunda + 1);
+            }
+        }
+        if let Some(jadwal) = self.jadwal_bom {
+            let tertunda = self.papan.bom_tertunda.borrow().len() as u32;
+            let giliran_bom = jadwal.setiap > 0 && self.langkah.is_multiple_of(jadwal.setiap);
+            if langkah && giliran_bom && self.papan.jumlah_bom() + tertunda < jadwal.maks {
+                self.papan.bom_tertunda.borrow_mut().push(jadwal.hitungan);
+            }
+        }
+
+        let mut cokelat_menyebar = None;
+        if langkah && kaskade.iter().all(|ksk| ksk.cokelat_hancur.is_empty()) {
+            cokelat_menyebar = self.papan.sebarkan_cokelat();
+        }
+
+        let mut bom_meledak = vec![];
+        if langkah && !self.sasaran_tercapai() {
+            bom_meledak = self.papan.hitung_mundur_bom();
+            self.bom_meledak.extend(bom_meledak.iter().cloned());
+        }
+
+        let hasil = HasilGiliran {
+            kaskade,
+            skor,
+            bonus_waktu_ms,
+            penghalang_muncul,
+            cokelat_menyebar,
+            bom_meledak,
+            tukar: None,
+        };
+        self.catat_peristiwa(&hasil);
+        hasil
+    }
+
+    // Mencatat peristiwa dari giliran yang sudah selesai diremukan
+    fn catat_peristiwa(&mut self, hasil: &HasilGiliran) {
+        for ksk in hasil.kaskade.iter() {
+            self.peristiwa.push(Peristiwa::TingkatKaskade(ksk.tingkat));
+            self.peristiwa.extend(ksk.kelompok.iter().cloned().map(Peristiwa::DempetDitemukan));
+            self.peristiwa.extend(ksk.spesial_dipicu.iter().cloned().map(|(pos, jenis)| Peristiwa::SpesialDipicu(pos, jenis)));
+            self.peristiwa.extend(ksk.spesial_dibuat.iter().cloned().map(|(pos, jenis)| Peristiwa::SpesialDibuat(pos, jenis)));
+            self.peristiwa.extend(ksk.penghalang_dipukul.iter().map(|pos| Peristiwa::PenghalangDipukul(*pos)));
+            self.peristiwa.extend(ksk.penghalang_hancur.iter().map(|pos| Peristiwa::PenghalangHancur(*pos)));
+            self.peristiwa.extend(ksk.cokelat_hancur.iter().map(|pos| Peristiwa::CokelatHancur(*pos)));
+            self.peristiwa.extend(ksk.kunci_dibuka.iter().map(|pos| Peristiwa::KunciDibuka(*pos)));
+            self.peristiwa.extend(ksk.bahan_terkumpul.iter().map(|pos| Peristiwa::BahanTerkumpul(*pos)));
+            self.peristiwa.extend(ksk.bom_dijinakkan.iter().map(|pos| Peristiwa::BomDijinakkan(*pos)));
+            self.peristiwa.extend(ksk.jeli_dibersihkan.iter().map(|(pos, sisa)| Peristiwa::JeliDibersihkan(*pos, *sisa)));
+            self.peristiwa.extend(ksk.jatuh.iter().map(|(dari, ke)| Peristiwa::PermenJatuh(*dari, *ke)));
+            self.peristiwa.extend(ksk.muncul.iter().cloned().map(|(pos, warna)| Peristiwa::PermenMuncul(pos, warna)));
+            self.peristiwa.push(Peristiwa::SkorBertambah(ksk.skor()));
+        }
+        if let Some((asal, ditelan)) = hasil.cokelat_menyebar {
+            self.peristiwa.push(Peristiwa::CokelatMenyebar(asal, ditelan));
+        }
+        self.peristiwa.extend(hasil.bom_meledak.iter().map(|pos| Peristiwa::BomMeledak(*pos)));
+        if self.sasaran.is_some() {
+            self.peristiwa.push(Peristiwa::KemajuanSasaran(self.kemajuan()));
+        }
+        if self.selesai() {
+            self.peristiwa.push(Peristiwa::PermainanSelesai(self.ringkasan()));
+        }
+    }
+
+    /// Mengambil semua peristiwa yang belum diambil, berurutan dari yang paling awal.
+    /// Peristiwa terus ditampung sampai diambil
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Peristiwa, Sesi};
+    /// let mut sesi = Sesi::new(Papan::new(7, 3));
+    /// let _ = sesi.tukar((0, 0), (5, 5));
+    /// let peristiwa = sesi.kuras_peristiwa();
+    /// assert_eq!(peristiwa[0], Peristiwa::TukarDicoba((0, 0), (5, 5)));
+    /// assert!(matches!(peristiwa[1], Peristiwa::TukarDitolak(..)));
+    /// assert!(sesi.kuras_peristiwa().is_empty());
+    /// ```
+    pub fn kuras_peristiwa(&mut self) -> Vec<Peristiwa> {
+        std::mem::take(&mut self.peristiwa)
+    }
+
+    /// Meremukan satu permen pilihan dengan palu
+    pub fn pakai_palu(&mut self, pos: Posisi) -> Result<HasilGiliran, Galat> {
+        self.rekam(|sesi| {
+            if sesi.selesai() { return Err(Galat::PermainanSelesai) }
+            if pos.0 >= sesi.papan.ukuran || pos.1 >= sesi.papan.ukuran {
+                return Err(Galat::DiLuarPapan(pos));
+            }
+            if !sesi.papan.punya_permen(pos) || sesi.papan.punya_bahan(pos) {
+                return Err(Galat::BukanPermen(pos));
+            }
+            sesi.persediaan.ambil(Penguat::Palu)?;
+
+            let kaskade = sesi.papan.remukan_satu(pos);
+            Ok(sesi.akhiri_giliran(kaskade, false))
+        })
+    }
+
+    /// Menukar dua permen bersebelahan walaupun tidak menghasilkan permen dempet
+    pub fn pakai_tukar_bebas(&mut self, pos1: Posisi, pos2: Posisi) -> Result<HasilGiliran, Galat> {
+        self.rekam(|sesi| {
+            if sesi.selesai() { return Err(Galat::PermainanSelesai) }
+            sesi.cek_penukaran(pos1, pos2)?;
+            sesi.persediaan.ambil(Penguat::TukarBebas)?;
+
+            sesi.papan.tukar(pos1, pos2);
+            let tukar = sesi.fase_tukar(pos1, pos2);
+            let kaskade = sesi.papan.remukan_kaskade(&[pos1, pos2]);
+            Ok(HasilGiliran { tukar: Some(tukar), ..sesi.akhiri_giliran(kaskade, false) })
+        })
+    }
+
+    /// Mengacak ulang letak permen di papan. Bisa dipakai walaupun papan sudah buntu.
+    /// Penguat hanya terpakai jika pengocokan berhasil
+    pub fn pakai_kocok(&mut self) -> Result<HasilGiliran, Galat> {
+        self.rekam(|sesi| {
+            if sesi.berakhir() { return Err(Galat::PermainanSelesai) }
+            if sesi.persediaan.jumlah(Penguat::Kocok) == 0 {
+                return Err(Galat::PenguatHabis(Penguat::Kocok));
+            }
+            if !sesi.papan.kocok() { return Err(Galat::GagalDikocok) }
+            sesi.persediaan.ambil(Penguat::Kocok)?;
+
+            sesi.peristiwa.push(Peristiwa::Dikocok);
+            let kaskade = sesi.papan.remukan_kaskade(&[]);
+            Ok(sesi.akhiri_giliran(kaskade, false))
+        })
+    }
+
+    /// Menambah 5 langkah. Bisa dipakai walaupun langkahnya sudah habis
+    pub fn pakai_tambah_langkah(&mut self) -> Result<HasilGiliran, Galat> {
+        self.rekam(|sesi| {
+            let Some(batas) = sesi.batas_langkah else {
+                return Err(Galat::TanpaBatasLangkah);
+            };
+            if sesi.sisa_waktu_ms == Some(0) || !sesi.bom_meledak.is_empty() || sesi.sasaran_tercapai() {
+                return Err(Galat::PermainanSelesai);
+            }
+            sesi.persediaan.ambil(Penguat::TambahLangkah)?;
+
+            sesi.batas_langkah = Some(batas.max(sesi.langkah) + TAMBAHAN_LANGKAH);
+            Ok(sesi.akhiri_giliran(vec![], false))
+        })
+    }
+
+    /// Menyesuaikan palet papan dengan tingkat kesulitan dari skor saat ini
+    fn terapkan_kesulitan(&mut self) {
+        let tingkat = Kesulitan::tingkat(self.skor);
+        let kesulitan = &TINGKAT_KESULITAN[tingkat];
+        self.kesulitan = Some(tingkat);
+        self.papan.ragam_warna = kesulitan.bobot_warna.len();
+        self.papan.palet = kesulitan.palet();
+    }
+
+    /// Bonus waktu dari dempet besar (>= 4 permen) dan dari setiap
+    /// tingkat kaskade setelah tingkat pertama
+    pub fn bonus_waktu(kaskade: &[Kaskade]) -> u64 {
+        let dempet_besar = kaskade
+            .iter()
+            .flat_map(|ksk| ksk.kelompok.iter())
+            .filter(|klp| klp.dempet.len() >= DEMPET_BESAR)
+            .count() as u64;
+        let kaskade_lanjutan = kaskade.len().saturating_sub(1) as u64;
+
+        dempet_besar * BONUS_DEMPET_BESAR_MS + kaskade_lanjutan * BONUS_KASKADE_MS
+    }
+}
+
+/// Aturan kapan permainan berakhir
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mode {
+    /// Bermain terus sampai tidak ada permen yang bisa ditukar
+    #[default]
+    Bebas,
+    /// Permainan berakhir setelah sejumlah langkah
+    Langkah(u32),
+    /// Permainan berakhir setelah sejumlah milidetik
+    Waktu(u64),
+    /// Makin sulit seiring skor, lihat TINGKAT_KESULITAN
+    TanpaAkhir,
+}
+
+/// Pilihan untuk membuat permainan baru
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pilihan {
+    pub ukuran: usize,
+    /// Jumlah warna yang muncul, 3 sampai 7
+    pub ragam_warna: usize,
+    /// Benih untuk isian papan, None berarti acak setiap kali permainan dibuat
+    pub benih: Option<u64>,
+    pub mode: Mode,
+    /// Jumlah giliran yang bisa diurungkan, 0 mematikan riwayat
+    pub batas_riwayat: usize,
+}
+
+impl Default for Pilihan {
+    fn default() -> Self {
+        Pilihan { ukuran: 7, ragam_warna: SEMUA_WARNA.len(), benih: None, mode: Mode::Bebas, batas_riwayat: 0 }
+    }
+}
+
+impl Pilihan {
+    /// Membuat sesi baru sesuai pilihan ini
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Mode, Pilihan};
+    /// let pilihan = Pilihan { ukuran: 8, ragam_warna: 5, benih: Some(7), mode: Mode::Langkah(20), ..Pilihan::default() };
+    /// let sesi = pilihan.buat_sesi();
+    /// assert_eq!(sesi.papan.ukuran, 8);
+    /// assert_eq!(sesi.papan.palet.len(), 5);
+    /// assert_eq!(sesi.sisa_langkah(), Some(20));
+    /// assert_eq!(sesi.papan.to_string(), pilihan.buat_sesi().papan.to_string());
+    /// ```
+    pub fn buat_sesi(&self) -> Sesi {
+        let ragam_warna = self.ragam_warna.clamp(3, SEMUA_WARNA.len());
+        let mut papan = Papan::dengan_benih(self.ukuran, ragam_warna, self.benih.unwrap_or_else(rand::random));
+        if ragam_warna < SEMUA_WARNA.len() {
+            papan.palet = SEMUA_WARNA[..ragam_warna].iter().map(|warna| (warna.clone(), 1)).collect();
+            papan.id_berikutnya.set(0);
+            let semua_posisi: Vec<Posisi> =
+                (0..papan.ukuran).flat_map(|y| (0..papan.ukuran).map(move |x| (y, x))).collect();
+            papan.acak_isian(&semua_posisi);
+            if papan.cek_kemungkinan().is_empty() {
+                papan.kocok();
+            }
+        }
+
+        let sesi = match self.mode {
+            Mode::Bebas => Sesi::new(papan),
+            Mode::Langkah(batas) => Sesi::new(papan).dengan_batas_langkah(batas),
+            Mode::Waktu(durasi_ms) => Sesi::berwaktu(papan, durasi_ms),
+            Mode::TanpaAkhir => Sesi::tanpa_akhir(papan),
+        };
+        match self.batas_riwayat {
+            0 => sesi,
+            batas => sesi.dengan_riwayat(batas),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vektor_warna() -> Vec<Warna> {
+        vec![
+            Warna::Merah,
+            Warna::Jingga,
             Warna::Kuning,
             Warna::Hijau,
             Warna::Biru,
@@ -482,231 +2789,1503 @@ mod tests {
     }
 
     #[test]
-    fn permen_new() {
-        let permen = Permen::new(Warna::Biru);
-        assert_eq!(*permen.warna.borrow(), Warna::Biru);
+    fn permen_new() {
+        let permen = Permen::new(Warna::Biru);
+        assert_eq!(*permen.warna.borrow(), Warna::Biru);
+    }
+
+    #[test]
+    fn permen_get_warna() {
+        let permen = Permen::new(Warna::Biru);
+        assert_eq!(permen.get_warna(), Warna::Biru);
+    }
+
+    #[test]
+    fn permen_set_warna() {
+        let permen = Permen::new(Warna::Biru);
+        permen.set_warna(Warna::Merah);
+        assert_eq!(permen.get_warna(), Warna::Merah);
+    }
+
+    #[test]
+    fn permen_acak_warna() {
+        let permen = Permen::new(Warna::Merah);
+        permen.acak_warna(&mut rand::thread_rng());
+
+        for _ in 0..100 {
+            assert_ne!(permen.get_warna(), Warna::Merah);
+        }
+    }
+
+    #[test]
+    fn dempet_new() {
+        let dempet = Dempet::new();
+        assert_eq!(dempet.vektor, vec![]);
+    }
+
+    #[test]
+    fn dempet_tambah() {
+        let mut dempet = Dempet::new();
+        dempet.tambah((0, 0)).tambah((1, 1));
+        assert_eq!(dempet.len(), 2);
+        dempet.tambah((0,0));
+        assert_eq!(dempet.len(), 2);
+    }
+
+    #[test]
+    fn dempet_kosongkan() {
+        let mut dempet = Dempet::new();
+        dempet.tambah((0, 0)).tambah((1, 1));
+        dempet.kosongkan();
+        assert_eq!(dempet.len(), 0);
+    }
+
+    #[test]
+    fn dempet_tambah_vektor() {
+        let mut dempet = Dempet::new();
+        dempet.tambah_vek(vec![(1, 0), (3, 0), (2, 0)]);
+        assert_eq!(dempet.len(), 3);
+    }
+
+    #[test]
+    fn dempet_urutkan() {
+        let mut dempet = Dempet::new();
+        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
+        dempet.urutkan();
+        assert_eq!(dempet.vektor, vec![(1, 0), (2, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn dempet_len() {
+        let mut dempet = Dempet::new();
+        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
+        assert_eq!(dempet.len(), dempet.vektor.len());
+    }
+
+    #[test]
+    fn dempet_kolom_berubah() {
+        let mut dempet = Dempet::new();
+        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
+        dempet.tambah_vek(vec![(1, 0), (1, 1), (1, 2)]);
+        let kolom_berubah = dempet.kolom_berubah();
+        assert_eq!(kolom_berubah, vec![(3, 0), (3, 1), (3, 2)]);
+    }
+
+    #[test]
+    fn papan_new() {
+        let papan = Papan::new(5, 3);
+        assert_eq!(papan.isi.len(), 5);
+        assert_eq!(papan.isi[0].len(), 5);
+
+        // assert isi dari papan adalah Warna
+        let vektor_warna = vektor_warna();
+        for baris in papan.isi.iter() {
+            for permen in baris.iter() {
+                assert!(vektor_warna.iter().any(|warna| {
+                    permen.get_warna() == *warna
+                }));
+            }
+        }
+    }
+
+    #[test]
+    fn papan_isian_baru() {
+        let isian = Papan::isian_baru(3, &mut rand::thread_rng());
+
+        // assert isi dari isian adalah Warna
+        let vektor_warna = vektor_warna();
+        for baris in isian.iter() {
+            for permen in baris.iter() {
+                assert!(vektor_warna.iter().any(|warna| {
+                    permen.get_warna() == *warna
+                }));
+            }
+        }
+
+        // assert tidak mungkin ada dempet (3 warna yang sama bersebelahan)
+        for _ in 0..100 {
+            let isian = Papan::isian_baru(3, &mut rand::thread_rng());
+            let warna0 = isian[0][0].get_warna();
+            let warna1 = isian[0][1].get_warna();
+            let warna2 = isian[0][2].get_warna();
+            assert!( !(warna0 == warna1 && warna1 == warna2) );
+        }
+    }
+
+    #[test]
+    fn papan_get_warna() {
+        let papan = Papan::new(5, 3);
+        let warna = papan.isi[0][0].warna.borrow().clone();
+        assert_eq!(papan.get_warna((0, 0)), warna);
+    }
+
+    #[test]
+    fn papan_set_warna() {
+        let papan = Papan::new(5, 3);
+        papan.set_warna((0, 0), Warna::Merah);
+        assert_eq!(papan.get_warna((0, 0)), Warna::Merah);
+    }
+
+    #[test]
+    fn papan_tukar() {
+        let papan = Papan::new(3, 3);
+        let warna0 = papan.get_warna((0, 0));
+        papan.tukar((0,0), (0,1));
+        let warna1 = papan.get_warna((0, 1));
+        assert_eq!(warna0, warna1);
+    }
+
+    #[test]
+    fn papan_tukar_diagonal() {
+        let papan: Papan = "R J K\nK H J\nJ K R".parse().unwrap();
+        papan.tukar((0, 0), (1, 1));
+        papan.tukar((0, 0), (0, 2));
+        assert_eq!(format!("{:#}", papan), "R J K \nK H J \nJ K R \n");
+    }
+
+    #[test]
+    fn papan_remukan() {
+        let papan = Papan::new(3, 3);
+        let warna0 = papan.get_warna((0, 0));
+        let warna1 = papan.get_warna((1, 0));
+        papan.remukan((2, 0));
+        assert_eq!(papan.get_warna((2, 0)), warna1);
+        assert_eq!(papan.get_warna((1, 0)), warna0);
+    }
+
+    #[test]
+    fn papan_cek_horizontal() {
+        let papan = Papan::new(7, 3);
+        for i in 0..papan.ukuran {
+            papan.set_warna((0, i), Warna::Merah);
+        }
+        let dempet = papan.cek_horizontal((0, 0), 3);
+        assert_eq!(dempet.len(), 7);
+
+        papan.set_warna((0, 3), Warna::Biru);
+        let dempet = papan.cek_horizontal((0, 0), 3);
+        assert_eq!(dempet.len(), 3);
+        let dempet = papan.cek_horizontal((0, 6), 3);
+        assert_eq!(dempet.len(), 3);
+    }
+
+    #[test]
+    fn papan_cek_vertikal() {
+        let papan = Papan::new(7, 3);
+        for i in 0..papan.ukuran {
+            papan.set_warna((i, 0), Warna::Merah);
+        }
+        let dempet = papan.cek_vertikal((0, 0), 3);
+        assert_eq!(dempet.len(), 7);
+
+        papan.set_warna((3, 0), Warna::Biru);
+        let dempet = papan.cek_vertikal((0, 0), 3);
+        assert_eq!(dempet.len(), 3);
+        let dempet = papan.cek_vertikal((6, 0), 3);
+        assert_eq!(dempet.len(), 3);
+    }
+
+    #[test]
+    fn papan_cek_dempet() {
+        let papan = Papan::new(7, 3);
+        for i in 0..papan.ukuran {
+            papan.set_warna((i, 0), Warna::Merah);
+            papan.set_warna((0, i), Warna::Merah);
+        }
+        let dempet = papan.cek_dempet((0, 0), 3);
+        assert_eq!(dempet.len(), 13);
+    }
+
+    #[test]
+    fn papan_mungkin_ditukar() {
+        let papan = Papan::new(5, 3);
+        papan.set_warna((0, 0), Warna::Merah);
+        papan.set_warna((1, 1), Warna::Merah);
+        papan.set_warna((1, 2), Warna::Merah);
+        assert!(papan.mungkin_ditukar((0, 0)));
+    }
+
+    #[test]
+    #[allow(clippy::len_zero)]
+    fn papan_cek_kemungkinan() {
+        let papan = Papan::new(5, 3);
+        papan.set_warna((0, 0), Warna::Merah);
+        papan.set_warna((1, 1), Warna::Merah);
+        papan.set_warna((1, 2), Warna::Merah);
+        assert!(papan.cek_kemungkinan().len() >= 1);
+    }
+
+    #[test]
+    fn papan_cek_kemungkinan_str() {
+        let papan = Papan::new(5, 3);
+        let mut result = String::new();
+        let vektor_kemungkinan = papan.cek_kemungkinan();
+        for pos in vektor_kemungkinan {
+            result.push_str(&format!("{}:{} ", pos.0, pos.1))
+        }
+        assert_eq!(papan.cek_kemungkinan_str(), result);
+    }
+
+    // Papan dengan pola warna yang tidak punya dempet dan tidak punya
+    // kemungkinan penukaran, sehingga hasil penukaran bisa diatur sendiri
+    fn papan_tanpa_dempet(ukuran: usize) -> Papan {
+        let papan = Papan::dengan_benih(ukuran, 3, 0);
+        let vektor_warna = vektor_warna();
+        for y in 0..ukuran {
+            for x in 0..ukuran {
+                papan.set_warna((y, x), vektor_warna[(x + 3 * y) % 7].clone());
+            }
+        }
+        papan
+    }
+
+    // Papan tanpa dempet dengan satu permen di pojok kanan bawah yang bisa ditukar,
+    // agar sesinya tidak langsung selesai karena buntu
+    fn papan_bisa_main(ukuran: usize) -> Papan {
+        let papan = papan_tanpa_dempet(ukuran);
+        let warna = papan.get_warna((ukuran - 2, ukuran - 3));
+        papan.set_warna((ukuran - 1, ukuran - 2), warna.clone());
+        papan.set_warna((ukuran - 1, ukuran - 1), warna);
+        assert!(papan.semua_dempet().is_empty());
+        assert!(papan.cek_kemungkinan().contains(&(ukuran - 2, ukuran - 3)));
+        papan
+    }
+
+    #[test]
+    fn papan_semua_dempet() {
+        let papan = papan_tanpa_dempet(7);
+        assert!(papan.semua_dempet().is_empty());
+        assert!(papan.cek_kemungkinan().is_empty());
+
+        // bentuk L yang bersinggungan di (2, 0) menjadi satu Dempet
+        for i in 0..3 {
+            papan.set_warna((i, 0), Warna::Merah);
+            papan.set_warna((2, i), Warna::Merah);
+        }
+        // deretan terpisah di baris terakhir
+        for i in 4..7 {
+            papan.set_warna((6, i), Warna::Biru);
+        }
+        let semua_dempet = papan.semua_dempet();
+        assert_eq!(semua_dempet.len(), 2);
+        assert!(semua_dempet.iter().any(|dempet| dempet.len() == 5));
+        assert!(semua_dempet.iter().any(|dempet| dempet.len() == 3));
+    }
+
+    #[test]
+    fn papan_remukan_kaskade() {
+        let papan = papan_tanpa_dempet(7);
+        for i in 0..4 {
+            papan.set_warna((3, i), Warna::Merah);
+        }
+        let kaskade = papan.remukan_kaskade(&[(3, 2)]);
+        assert!(!kaskade.is_empty());
+        assert_eq!(kaskade[0].tingkat, 1);
+        assert_eq!(kaskade[0].kelompok[0].warna, Warna::Merah);
+        assert_eq!(kaskade[0].kelompok[0].dempet.len(), 4);
+
+        // satu permen menjadi permen garis, tiga lainnya remuk
+        assert_eq!(kaskade[0].spesial_dibuat, vec![((3, 2), Jenis::GarisMendatar)]);
+        assert_eq!(kaskade[0].jumlah_permen(), 3);
+        assert_eq!(kaskade[0].skor(), 3 * SKOR_PER_PERMEN);
+        assert!(papan.semua_dempet().is_empty());
+    }
+
+    #[test]
+    fn papan_remukan_kaskade_spesial() {
+        let papan = papan_tanpa_dempet(7);
+        for i in 0..3 {
+            papan.set_warna((3, i), Warna::Merah);
+        }
+        papan.set_jenis((3, 0), Jenis::GarisTegak);
+
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert_eq!(kaskade[0].spesial_dipicu, vec![((3, 0), Jenis::GarisTegak)]);
+        // 3 permen dempet dan 6 permen lain di kolom 0
+        assert_eq!(kaskade[0].jumlah_permen(), 9);
+        assert!(kaskade[0].remuk.iter().any(|(pos, _)| *pos == (6, 0)));
+    }
+
+    #[test]
+    fn jenis_dari_dempet() {
+        let mut dempet = Dempet::new();
+        dempet.tambah_vek(vec![(0, 1), (1, 1), (2, 1)]);
+        assert_eq!(Jenis::dari_dempet(&dempet), None);
+        dempet.tambah((3, 1));
+        assert_eq!(Jenis::dari_dempet(&dempet), Some(Jenis::GarisTegak));
+        dempet.tambah((3, 2));
+        assert_eq!(Jenis::dari_dempet(&dempet), Some(Jenis::Bungkus));
+    }
+
+    #[test]
+    fn jenis_wilayah() {
+        assert!(Jenis::Biasa.wilayah((1, 1), 5).is_empty());
+        assert_eq!(Jenis::GarisMendatar.wilayah((1, 2), 3), vec![(1, 0), (1, 1), (1, 2)]);
+        assert_eq!(Jenis::GarisTegak.wilayah((1, 2), 3), vec![(0, 2), (1, 2), (2, 2)]);
+        assert_eq!(Jenis::Bungkus.wilayah((2, 2), 5).len(), 9);
+        assert_eq!(Jenis::Bungkus.wilayah((0, 0), 5), vec![(0, 0), (0, 1), (1, 0), (1, 1)]);
+    }
+
+    #[test]
+    fn sesi_tick() {
+        let mut sesi = Sesi::berwaktu(papan_bisa_main(5), 1_000);
+        sesi.tick(400);
+        assert_eq!(sesi.sisa_waktu_ms, Some(600));
+        assert!(!sesi.selesai());
+        sesi.tick(700);
+        assert_eq!(sesi.sisa_waktu_ms, Some(0));
+        assert!(sesi.selesai());
+
+        let mut sesi = Sesi::new(papan_bisa_main(5));
+        sesi.tick(1_000_000);
+        assert!(!sesi.selesai());
+    }
+
+    #[test]
+    fn sesi_tukar_ditolak() {
+        let mut sesi = Sesi::new(papan_bisa_main(5));
+        assert_eq!(sesi.tukar((0, 0), (1, 1)), Err(Galat::TidakBersebelahan((0, 0), (1, 1))));
+        assert_eq!(sesi.tukar((0, 4), (0, 5)), Err(Galat::DiLuarPapan((0, 5))));
+
+        let warna0 = sesi.papan.get_warna((0, 0));
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::TidakAdaDempet));
+        assert_eq!(sesi.papan.get_warna((0, 0)), warna0);
+        assert_eq!(sesi.langkah, 0);
+    }
+
+    #[test]
+    fn sesi_tukar_bonus_waktu() {
+        let mut sesi = Sesi::berwaktu(papan_tanpa_dempet(7), 10_000);
+        // tukar (4, 3) ke (3, 3) menjadi 4 merah di baris 3
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert_eq!(hasil.kaskade[0].kelompok[0].dempet.len(), 4);
+        assert_eq!(hasil.kaskade[0].spesial_dibuat, vec![((3, 3), Jenis::GarisMendatar)]);
+        assert!(hasil.bonus_waktu_ms >= BONUS_DEMPET_BESAR_MS);
+        assert_eq!(hasil.bonus_waktu_ms, Sesi::bonus_waktu(&hasil.kaskade));
+        assert_eq!(sesi.sisa_waktu_ms, Some(10_000 + hasil.bonus_waktu_ms));
+        assert_eq!(sesi.skor, hasil.skor);
+        assert_eq!(sesi.langkah, 1);
+    }
+
+    #[test]
+    fn warna_indeks() {
+        for (i, warna) in vektor_warna().iter().enumerate() {
+            assert_eq!(warna.indeks(), i);
+        }
+    }
+
+    #[test]
+    fn sasaran_tercapai() {
+        let mut statistik = Statistik::default();
+        statistik.remuk_warna[Warna::Biru.indeks()] = 12;
+
+        let dan = Sasaran::Dan(vec![Sasaran::Skor(500), Sasaran::RemukanWarna(Warna::Biru, 10)]);
+        assert!(!dan.tercapai(400, &statistik));
+        assert!(dan.tercapai(500, &statistik));
+
+        let atau = Sasaran::Atau(vec![Sasaran::Skor(500), Sasaran::BersihkanJeli(3)]);
+        assert!(!atau.tercapai(400, &statistik));
+        statistik.jeli_dibersihkan = 3;
+        assert!(atau.tercapai(400, &statistik));
+
+        let bersarang = Sasaran::Atau(vec![dan, Sasaran::TurunkanBahan(2)]);
+        assert!(!bersarang.tercapai(0, &statistik));
+        statistik.bahan_terkumpul = 2;
+        assert!(bersarang.tercapai(0, &statistik));
+    }
+
+    #[test]
+    fn sasaran_kemajuan() {
+        let mut statistik = Statistik::default();
+        statistik.remuk_warna[Warna::Merah.indeks()] = 25;
+        let sasaran = Sasaran::Dan(vec![
+            Sasaran::Skor(1000),
+            Sasaran::Atau(vec![Sasaran::RemukanWarna(Warna::Merah, 20), Sasaran::TurunkanBahan(1)]),
+        ]);
+
+        let kemajuan = sasaran.kemajuan(300, &statistik);
+        assert_eq!(kemajuan.len(), 3);
+        assert_eq!((kemajuan[0].nilai, kemajuan[0].target), (300, 1000));
+        assert_eq!(kemajuan[1].sasaran, Sasaran::RemukanWarna(Warna::Merah, 20));
+        assert_eq!((kemajuan[1].nilai, kemajuan[1].target), (20, 20));
+        assert!(kemajuan[1].tercapai());
+        assert!(!kemajuan[2].tercapai());
+    }
+
+    #[test]
+    fn sesi_kemajuan() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7))
+            .dengan_sasaran(Sasaran::RemukanWarna(Warna::Merah, 1000));
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        let merah = hasil.kaskade
+            .iter()
+            .flat_map(|ksk| ksk.remuk.iter())
+            .filter(|(_, warna)| *warna == Warna::Merah)
+            .count() as u32;
+        assert!(merah >= 3);
+        assert_eq!(sesi.kemajuan()[0].nilai, merah);
+        assert_eq!(sesi.kemajuan_str(), format!("🟥:{}/1000 ", merah));
+        assert!(!sesi.selesai());
+
+        sesi.sasaran = Some(Sasaran::RemukanWarna(Warna::Merah, 3));
+        assert!(sesi.sasaran_tercapai());
+        assert!(sesi.selesai());
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::PermainanSelesai));
+    }
+
+    #[test]
+    fn sesi_bintang() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(5));
+        assert_eq!(sesi.bintang(), 0);
+        sesi.skor = 1_000;
+        assert_eq!(sesi.bintang(), 0);
+
+        let mut sesi = sesi.dengan_ambang_bintang([100, 200, 300]);
+        sesi.skor = 0;
+        assert_eq!(sesi.bintang(), 0);
+        sesi.skor = 250;
+        assert_eq!(sesi.bintang(), 2);
+        sesi.skor = 300;
+        assert_eq!(sesi.bintang(), 3);
+
+        sesi.sasaran = Some(Sasaran::RemukanWarna(Warna::Biru, 10));
+        assert_eq!(sesi.bintang(), 0);
+        sesi.statistik.remuk_warna[Warna::Biru.indeks()] = 10;
+        assert_eq!(sesi.bintang(), 3);
+    }
+
+    #[test]
+    fn sesi_ringkasan() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7))
+            .dengan_sasaran(Sasaran::Skor(10))
+            .dengan_ambang_bintang([10, 10_000, 20_000]);
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+
+        let ringkasan = sesi.ringkasan();
+        assert_eq!(ringkasan.skor, hasil.skor);
+        assert_eq!(ringkasan.langkah, 1);
+        assert_eq!(ringkasan.kaskade_terbesar, hasil.kaskade.len() as u32);
+        assert!(ringkasan.spesial_dibuat >= 1);
+        assert!(ringkasan.sasaran_tercapai);
+        assert!(ringkasan.bintang >= 1);
+    }
+
+    #[test]
+    fn ringkasan_parse() {
+        let ringkasan = Ringkasan {
+            skor: 1200,
+            bintang: 2,
+            langkah: 14,
+            kaskade_terbesar: 3,
+            spesial_dibuat: 2,
+            sasaran_tercapai: true,
+        };
+        let teks = "skor:1200 bintang:2 langkah:14 kaskade:3 spesial:2 sasaran:1";
+        assert_eq!(ringkasan.to_string(), teks);
+        assert_eq!(teks.parse::<Ringkasan>(), Ok(ringkasan));
+
+        assert_eq!("skor:12 bintang:4".parse::<Ringkasan>(), Err(Galat::FormatTidakSah("bintang:4".to_string())));
+        assert!("skor".parse::<Ringkasan>().is_err());
+        assert!("nyawa:3".parse::<Ringkasan>().is_err());
+    }
+
+    #[test]
+    fn permen_tukar() {
+        let permen = Permen::new(Warna::Merah);
+        let lain = Permen::new(Warna::Biru);
+        lain.set_jenis(Jenis::GarisTegak);
+        permen.tukar(&lain);
+        assert_eq!(permen.get_warna(), Warna::Biru);
+        assert_eq!(permen.get_jenis(), Jenis::GarisTegak);
+        assert_eq!(lain.get_warna(), Warna::Merah);
+        assert_eq!(lain.get_jenis(), Jenis::Biasa);
+    }
+
+    #[test]
+    fn warna_from_str() {
+        for warna in vektor_warna() {
+            assert_eq!(warna.nama().parse(), Ok(warna.clone()));
+            assert_eq!(warna.huruf().to_string().parse(), Ok(warna.clone()));
+            assert_eq!(warna.to_string().parse(), Ok(warna.clone()));
+        }
+        assert!("abu".parse::<Warna>().is_err());
+    }
+
+    #[test]
+    fn papan_warna_acak() {
+        let mut papan = Papan::new(5, 3);
+        papan.palet = vec![(Warna::Merah, 1), (Warna::Biru, 0), (Warna::Hijau, 5)];
+        for _ in 0..100 {
+            assert_ne!(papan.warna_acak(), Warna::Biru);
+        }
+    }
+
+    #[test]
+    fn papan_petak() {
+        let papan = papan_tanpa_dempet(5);
+        papan.set_petak((1, 1), Petak::Kosong);
+        papan.set_petak((1, 2), Petak::Penghalang(2));
+        assert_eq!(papan.get_petak((1, 2)), Petak::Penghalang(2));
+        assert!(papan.punya_permen((0, 0)));
+        assert!(!papan.punya_permen((1, 1)));
+        assert!(papan.tetap((1, 2)));
+        assert!(!papan.bisa_ditukar((1, 2)));
+        assert!(!papan.bisa_ditukar((5, 0)));
+        assert!(papan.to_string().lines().nth(1).unwrap().contains("⬛ ⬜"));
+    }
+
+    #[test]
+    fn papan_remukan_ruas() {
+        let papan = papan_tanpa_dempet(5);
+        papan.set_petak((2, 0), Petak::Penghalang(1));
+        let warna0 = papan.get_warna((0, 0));
+        let warna1 = papan.get_warna((1, 0));
+        let warna3 = papan.get_warna((3, 0));
+
+        // permen di atas penghalang tidak ikut jatuh
+        papan.remukan((4, 0));
+        assert_eq!(papan.get_warna((4, 0)), warna3);
+        assert_eq!(papan.get_warna((1, 0)), warna1);
+        assert_eq!(papan.get_warna((0, 0)), warna0);
+    }
+
+    #[test]
+    fn papan_cek_horizontal_penghalang() {
+        let papan = Papan::new(7, 3);
+        for i in 0..papan.ukuran {
+            papan.set_warna((0, i), Warna::Merah);
+        }
+        papan.set_petak((0, 3), Petak::Penghalang(1));
+        assert_eq!(papan.cek_horizontal((0, 0), 3).len(), 3);
+        assert_eq!(papan.cek_horizontal((0, 4), 3).len(), 3);
+        assert!(papan.cek_horizontal((0, 3), 3).is_empty());
+        assert!(papan.cek_vertikal((0, 3), 3).is_empty());
+    }
+
+    #[test]
+    fn papan_remukan_kaskade_penghalang() {
+        let papan = papan_tanpa_dempet(7);
+        for i in 0..3 {
+            papan.set_warna((3, i), Warna::Merah);
+        }
+        papan.set_petak((2, 1), Petak::Penghalang(2));
+        papan.set_petak((4, 1), Petak::Penghalang(1));
+
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert!(kaskade[0].penghalang_dipukul.contains(&(2, 1)));
+        assert!(kaskade[0].penghalang_dipukul.contains(&(4, 1)));
+        assert_ne!(papan.get_petak((2, 1)), Petak::Penghalang(2));
+        assert_eq!(papan.get_petak((4, 1)), Petak::Biasa);
+        assert_eq!(kaskade[0].penghalang_hancur, vec![(4, 1)]);
+    }
+
+    #[test]
+    fn papan_penghalang_berlapis() {
+        // dua permen remuk di sebelah penghalang yang sama hanya memukulnya sekali
+        let papan = papan_tanpa_dempet(7);
+        for i in 0..3 {
+            papan.set_warna((3, i), Warna::Merah);
+        }
+        papan.set_petak((2, 0), Petak::Penghalang(PENGHALANG_MAKS));
+        papan.set_petak((2, 1), Petak::Penghalang(PENGHALANG_MAKS));
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert!(kaskade[0].penghalang_hancur.is_empty());
+        assert_eq!(kaskade[0].penghalang_dipukul.iter().filter(|pos| **pos == (2, 0)).count(), 1);
+        assert_eq!(papan.get_petak((2, 0)), Petak::Penghalang(PENGHALANG_MAKS - 1));
+
+        assert_eq!(Petak::dari_kode(2, PENGHALANG_MAKS), Some(Petak::Penghalang(PENGHALANG_MAKS)));
+        assert_eq!(Petak::dari_kode(2, PENGHALANG_MAKS + 1), None);
+        assert_eq!(Petak::dari_kode(2, 0), None);
+
+        let papan = papan_tanpa_dempet(3);
+        let pos = papan.munculkan_penghalang(9).unwrap();
+        assert_eq!(papan.get_petak(pos), Petak::Penghalang(PENGHALANG_MAKS));
+    }
+
+    #[test]
+    fn sesi_batas_langkah() {
+        let mut sesi = Sesi::new(papan_bisa_main(7)).dengan_batas_langkah(1);
+        assert_eq!(sesi.sisa_langkah(), Some(1));
+        sesi.papan.set_petak((2, 2), Petak::Penghalang(1));
+        assert_eq!(sesi.tukar((2, 2), (2, 3)), Err(Galat::TidakBisaDitukar((2, 2))));
+
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        assert!(sesi.tukar((4, 3), (3, 3)).is_ok());
+        assert_eq!(sesi.sisa_langkah(), Some(0));
+        assert!(sesi.selesai());
+    }
+
+    #[test]
+    fn kesulitan_tingkat() {
+        assert_eq!(Kesulitan::tingkat(999), 0);
+        assert_eq!(Kesulitan::tingkat(1_000), 1);
+        assert_eq!(Kesulitan::tingkat(6_500), 3);
+        assert_eq!(Kesulitan::tingkat(u32::MAX), TINGKAT_KESULITAN.len() - 1);
+
+        // ragam warna bertambah sampai 7 dan penghalang tidak pernah berkurang
+        for pasangan in TINGKAT_KESULITAN.windows(2) {
+            assert!(pasangan[0].skor_minimal < pasangan[1].skor_minimal);
+            assert!(pasangan[0].bobot_warna.len() <= pasangan[1].bobot_warna.len());
+            assert!(pasangan[0].peluang_penghalang <= pasangan[1].peluang_penghalang);
+        }
+        assert_eq!(TINGKAT_KESULITAN[0].bobot_warna.len(), 4);
+        assert_eq!(TINGKAT_KESULITAN[TINGKAT_KESULITAN.len() - 1].bobot_warna.len(), 7);
+    }
+
+    #[test]
+    fn kesulitan_palet() {
+        let palet = TINGKAT_KESULITAN[1].palet();
+        assert_eq!(palet.len(), 5);
+        assert_eq!(palet[0], (Warna::Merah, 3));
+        assert_eq!(palet[4], (Warna::Biru, 2));
+    }
+
+    #[test]
+    fn sesi_tanpa_akhir() {
+        for benih in 0..20 {
+            let sesi = Sesi::tanpa_akhir(Papan::dengan_benih(7, 4, benih));
+            assert_eq!(sesi.kesulitan, Some(0));
+            assert_eq!(sesi.papan.palet, TINGKAT_KESULITAN[0].palet());
+            assert!(sesi.papan.semua_dempet().is_empty());
+            for y in 0..7 {
+                for x in 0..7 {
+                    assert!(sesi.papan.get_warna((y, x)).indeks() < 4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sesi_tanpa_akhir_naik_tingkat() {
+        let mut sesi = Sesi::tanpa_akhir(papan_tanpa_dempet(7));
+        sesi.papan = papan_tanpa_dempet(7);
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        sesi.skor = 6_000;
+
+        sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert!(sesi.kesulitan >= Some(3));
+        assert_eq!(sesi.papan.ragam_warna, 7);
     }
 
     #[test]
-    fn permen_get_warna() {
-        let permen = Permen::new(Warna::Biru);
-        assert_eq!(permen.get_warna(), Warna::Biru);
+    fn sesi_tanpa_akhir_selesai() {
+        let mut sesi = Sesi::tanpa_akhir(Papan::dengan_benih(5, 4, 0));
+        sesi.papan = papan_tanpa_dempet(5);
+        assert!(sesi.selesai());
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::PermainanSelesai));
     }
 
     #[test]
-    fn permen_set_warna() {
-        let permen = Permen::new(Warna::Biru);
-        permen.set_warna(Warna::Merah);
-        assert_eq!(permen.get_warna(), Warna::Merah);
+    fn papan_munculkan_penghalang() {
+        let papan = papan_tanpa_dempet(3);
+        for _ in 0..9 {
+            let pos = papan.munculkan_penghalang(2).unwrap();
+            assert_eq!(papan.get_petak(pos), Petak::Penghalang(2));
+        }
+        assert_eq!(papan.munculkan_penghalang(2), None);
     }
 
     #[test]
-    fn permen_acak_warna() {
-        let permen = Permen::new(Warna::Merah);
-        permen.acak_warna();
+    fn papan_remukan_satu() {
+        let papan = papan_tanpa_dempet(7);
+        let warna = papan.get_warna((3, 3));
+        let kaskade = papan.remukan_satu((3, 3));
+        assert_eq!(kaskade[0].tingkat, 1);
+        assert!(kaskade[0].kelompok.is_empty());
+        assert_eq!(kaskade[0].remuk, vec![((3, 3), warna)]);
+        assert!(papan.semua_dempet().is_empty());
+    }
 
-        for _ in 0..100 {
-            assert_ne!(permen.get_warna(), Warna::Merah);
+    #[test]
+    fn papan_kode() {
+        let papan = papan_tanpa_dempet(4);
+        papan.set_jenis((1, 2), Jenis::Bungkus);
+        papan.set_petak((3, 3), Petak::Kosong);
+        papan.set_jeli((0, 0), 2);
+        papan.set_kunci((1, 2), true);
+        papan.set_bom((2, 1), 7);
+        let kode = papan.kode();
+        assert_eq!(kode.len(), 2 + 16 * KODE_PER_PETAK);
+
+        let petak = |pos: Posisi| &kode[2 + (pos.0 * 4 + pos.1) * KODE_PER_PETAK..][..KODE_PER_PETAK];
+        assert_eq!(petak((0, 0)), [0, 0, papan.get_warna((0, 0)).indeks() as u8, 0, 2, 0, 0]);
+        assert_eq!(petak((1, 2)), [0, 0, papan.get_warna((1, 2)).indeks() as u8, 3, 0, 1, 0]);
+        assert_eq!(petak((2, 1)), [0, 0, papan.get_warna((2, 1)).indeks() as u8, 0, 0, 0, 7]);
+        assert_eq!(petak((3, 3)), [1, 0, KODE_TANPA_PERMEN, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn papan_jeli() {
+        let papan: Papan = "
+            R~~ J K J
+            R~ K J K
+            K J~ R K
+            J K J~~ R
+        ".parse().unwrap();
+        assert_eq!(papan.sisa_jeli(), 6);
+        papan.set_warna((2, 0), Warna::Merah);
+
+        // jeli tidak ikut jatuh, hanya berkurang satu lapis di petak yang permennya remuk
+        let kaskade = papan.remukan_kaskade(&[(2, 0)]);
+        assert_eq!(kaskade[0].jeli_dibersihkan, vec![((0, 0), 1), ((1, 0), 0)]);
+        let dibersihkan: usize = kaskade.iter().map(|ksk| ksk.jeli_dibersihkan.len()).sum();
+        assert_eq!(papan.sisa_jeli() as usize, 6 - dibersihkan);
+        assert_eq!(papan.get_jeli((1, 0)), 0);
+
+        papan.set_jeli((3, 3), 5);
+        assert_eq!(papan.get_jeli((3, 3)), JELI_MAKS);
+    }
+
+    #[test]
+    fn papan_rencana_jatuh() {
+        let papan: Papan = "
+            R J K H
+            K R . B
+            H #2 J J
+            B K R H
+        ".parse().unwrap();
+        let mut kosong = Dempet::new();
+        kosong.tambah_vek(vec![(3, 1), (3, 2), (1, 1)]);
+        kosong.urutkan();
+        let (jatuh, muncul) = papan.rencana_jatuh(&kosong);
+        // kolom 1 terpotong penghalang, kolom 2 terpotong lubang
+        assert_eq!(jatuh, vec![((0, 1), (1, 1)), ((2, 2), (3, 2))]);
+        assert_eq!(muncul, vec![(3, 1), (0, 1), (2, 2)]);
+
+        let warna = papan.get_warna((0, 1));
+        for pos in kosong.iter() {
+            papan.remukan(*pos);
         }
+        assert_eq!(papan.get_warna((1, 1)), warna);
+        assert_eq!(papan.get_warna((3, 2)), Warna::Jingga);
     }
 
     #[test]
-    fn dempet_new() {
-        let dempet = Dempet::new();
-        assert_eq!(dempet.vektor, vec![]);
+    fn papan_acak_isian() {
+        for benih in 0..50 {
+            let mut papan = Papan::dengan_benih(10, 3, benih);
+            papan.palet = SEMUA_WARNA[..3].iter().map(|warna| (warna.clone(), 1)).collect();
+            papan.set_petak((4, 4), Petak::Penghalang(1));
+            let semua_posisi: Vec<Posisi> =
+                (0..10).flat_map(|y| (0..10).map(move |x| (y, x))).filter(|pos| *pos != (4, 4)).collect();
+            assert!(papan.acak_isian(&semua_posisi));
+            assert!(papan.semua_dempet().is_empty());
+        }
     }
 
     #[test]
-    fn dempet_tambah() {
-        let mut dempet = Dempet::new();
-        dempet.tambah((0, 0)).tambah((1, 1));
-        assert_eq!(dempet.len(), 2);
-        dempet.tambah((0,0));
-        assert_eq!(dempet.len(), 2);
+    fn papan_kocok() {
+        let papan = Papan::new(7, 3);
+        papan.set_petak((0, 0), Petak::Penghalang(1));
+        let hitung_warna = |papan: &Papan| {
+            let mut jumlah = [0; 7];
+            for y in 0..7 {
+                for x in 0..7 {
+                    if papan.punya_permen((y, x)) {
+                        jumlah[papan.get_warna((y, x)).indeks()] += 1;
+                    }
+                }
+            }
+            jumlah
+        };
+        let sebelum = hitung_warna(&papan);
+
+        assert!(papan.kocok());
+        assert_eq!(hitung_warna(&papan), sebelum);
+        assert_eq!(papan.get_petak((0, 0)), Petak::Penghalang(1));
+        assert!(papan.semua_dempet().is_empty());
+        assert!(!papan.cek_kemungkinan().is_empty());
     }
 
     #[test]
-    fn dempet_kosongkan() {
-        let mut dempet = Dempet::new();
-        dempet.tambah((0, 0)).tambah((1, 1));
-        dempet.kosongkan();
-        assert_eq!(dempet.len(), 0);
+    fn sesi_pakai_palu() {
+        let mut sesi = Sesi::new(papan_bisa_main(7));
+        assert_eq!(sesi.pakai_palu((3, 3)), Err(Galat::PenguatHabis(Penguat::Palu)));
+
+        sesi.persediaan.tambah(Penguat::Palu, 1);
+        sesi.papan.set_petak((0, 0), Petak::Penghalang(1));
+        assert_eq!(sesi.pakai_palu((0, 0)), Err(Galat::BukanPermen((0, 0))));
+        assert_eq!(sesi.pakai_palu((7, 0)), Err(Galat::DiLuarPapan((7, 0))));
+        assert_eq!(sesi.persediaan.jumlah(Penguat::Palu), 1);
+
+        let hasil = sesi.pakai_palu((3, 3)).unwrap();
+        assert_eq!(hasil.kaskade[0].remuk[0].0, (3, 3));
+        assert_eq!(sesi.skor, hasil.skor);
+        assert!(sesi.skor > 0);
+        assert_eq!(sesi.langkah, 0);
+        assert_eq!(sesi.persediaan.jumlah(Penguat::Palu), 0);
     }
 
     #[test]
-    fn dempet_tambah_vektor() {
-        let mut dempet = Dempet::new();
-        dempet.tambah_vek(vec![(1, 0), (3, 0), (2, 0)]);
-        assert_eq!(dempet.len(), 3);
+    fn sesi_pakai_tukar_bebas() {
+        let mut sesi = Sesi::new(papan_bisa_main(7));
+        sesi.persediaan.tambah(Penguat::TukarBebas, 2);
+        assert_eq!(sesi.pakai_tukar_bebas((0, 0), (1, 1)), Err(Galat::TidakBersebelahan((0, 0), (1, 1))));
+
+        let warna0 = sesi.papan.get_warna((0, 0));
+        let warna1 = sesi.papan.get_warna((0, 1));
+        let hasil = sesi.pakai_tukar_bebas((0, 0), (0, 1)).unwrap();
+        assert!(hasil.kaskade.is_empty());
+        assert_eq!(sesi.papan.get_warna((0, 0)), warna1);
+        assert_eq!(sesi.papan.get_warna((0, 1)), warna0);
+        assert_eq!(sesi.langkah, 0);
+        assert_eq!(sesi.persediaan.jumlah(Penguat::TukarBebas), 1);
     }
 
     #[test]
-    fn dempet_urutkan() {
-        let mut dempet = Dempet::new();
-        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
-        dempet.urutkan();
-        assert_eq!(dempet.vektor, vec![(1, 0), (2, 0), (3, 0)]);
+    fn sesi_pakai_kocok() {
+        let mut sesi = Sesi::new(Papan::new(7, 3));
+        assert_eq!(sesi.pakai_kocok(), Err(Galat::PenguatHabis(Penguat::Kocok)));
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        let hasil = sesi.pakai_kocok().unwrap();
+        assert_eq!(sesi.skor, hasil.skor);
+        assert!(sesi.papan.semua_dempet().is_empty());
     }
 
     #[test]
-    fn dempet_len() {
-        let mut dempet = Dempet::new();
-        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
-        assert_eq!(dempet.len(), dempet.vektor.len());
+    fn sesi_pakai_kocok_buntu() {
+        // papan tanpa akhir yang buntu masih bisa dikocok
+        let mut sesi = Sesi::tanpa_akhir(Papan::dengan_benih(5, 4, 0));
+        sesi.papan = papan_tanpa_dempet(5);
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        assert!(sesi.buntu());
+        assert!(sesi.selesai());
+        sesi.pakai_kocok().unwrap();
+        assert!(!sesi.buntu());
+        assert!(!sesi.selesai());
+        assert_eq!(sesi.persediaan.jumlah(Penguat::Kocok), 0);
+
+        sesi.papan = papan_tanpa_dempet(5);
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        sesi.sisa_waktu_ms = Some(0);
+        assert_eq!(sesi.pakai_kocok(), Err(Galat::PermainanSelesai));
     }
 
     #[test]
-    fn dempet_kolom_berubah() {
-        let mut dempet = Dempet::new();
-        dempet.tambah_vek(vec![(3, 0), (1, 0), (2, 0)]);
-        dempet.tambah_vek(vec![(1, 0), (1, 1), (1, 2)]);
-        let kolom_berubah = dempet.kolom_berubah();
-        assert_eq!(kolom_berubah, vec![(3, 0), (3, 1), (3, 2)]);
+    fn sesi_buntu_bebas() {
+        // papan mode bebas yang buntu juga menyelesaikan permainan
+        let mut sesi = Sesi::new(papan_bisa_main(5));
+        assert!(!sesi.selesai());
+        sesi.papan = papan_tanpa_dempet(5);
+        assert!(sesi.buntu());
+        assert!(sesi.selesai());
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::PermainanSelesai));
+
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        sesi.pakai_kocok().unwrap();
+        assert!(!sesi.selesai());
     }
 
     #[test]
-    fn papan_new() {
-        let papan = Papan::new(5, 3);
-        assert_eq!(papan.isi.len(), 5);
-        assert_eq!(papan.isi[0].len(), 5);
+    fn sesi_pakai_kocok_gagal() {
+        // dua permen saja tidak pernah bisa ditukar menjadi dempet
+        let papan: Papan = ". . .\n. R J\n. . .".parse().unwrap();
+        let mut sesi = Sesi::new(papan);
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        assert_eq!(sesi.pakai_kocok(), Err(Galat::GagalDikocok));
+        assert_eq!(sesi.persediaan.jumlah(Penguat::Kocok), 1);
+        assert_eq!(sesi.papan.get_warna((1, 1)), Warna::Merah);
+        assert_eq!(sesi.papan.get_warna((1, 2)), Warna::Jingga);
+        assert!(sesi.kuras_peristiwa().is_empty());
+    }
 
-        // assert isi dari papan adalah Warna
-        let vektor_warna = vektor_warna();
-        for baris in papan.isi.iter() {
-            for permen in baris.iter() {
-                assert!(vektor_warna.iter().any(|warna| {
-                    permen.get_warna() == *warna
-                }));
-            }
-        }
+    #[test]
+    fn sesi_pakai_tambah_langkah() {
+        let mut sesi = Sesi::new(papan_bisa_main(7));
+        sesi.persediaan.tambah(Penguat::TambahLangkah, 1);
+        assert_eq!(sesi.pakai_tambah_langkah(), Err(Galat::TanpaBatasLangkah));
+
+        let mut sesi = sesi.dengan_batas_langkah(3);
+        sesi.langkah = 3;
+        assert!(sesi.selesai());
+        sesi.pakai_tambah_langkah().unwrap();
+        assert_eq!(sesi.sisa_langkah(), Some(5));
+        assert!(!sesi.selesai());
+        assert_eq!(sesi.pakai_tambah_langkah(), Err(Galat::PenguatHabis(Penguat::TambahLangkah)));
     }
 
     #[test]
-    fn papan_isian_baru() {
-        let isian = Papan::isian_baru(3);
+    fn sesi_riwayat() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7));
+        assert_eq!(sesi.urungkan(), Err(Galat::RiwayatMati));
 
-        // assert isi dari isian adalah Warna
-        let vektor_warna = vektor_warna();
-        for baris in isian.iter() {
-            for permen in baris.iter() {
-                assert!(vektor_warna.iter().any(|warna| {
-                    permen.get_warna() == *warna
-                }));
-            }
+        let mut sesi = sesi.dengan_riwayat(10);
+        assert_eq!(sesi.urungkan(), Err(Galat::RiwayatKosong));
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
         }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        let awal = sesi.papan.to_string();
 
-        // assert tidak mungkin ada dempet (3 warna yang sama bersebelahan)
-        for _ in 0..100 {
-            let isian = Papan::isian_baru(3);
-            let warna0 = isian[0][0].get_warna();
-            let warna1 = isian[0][1].get_warna();
-            let warna2 = isian[0][2].get_warna();
-            assert!( !(warna0 == warna1 && warna1 == warna2) );
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::TidakAdaDempet));
+        assert_eq!(sesi.riwayat.as_ref().unwrap().bisa_diurungkan(), 0);
+
+        sesi.tukar((4, 3), (3, 3)).unwrap();
+        let akhir = sesi.papan.to_string();
+        let skor = sesi.skor;
+        assert_eq!(sesi.papan.get_jenis((3, 3)), Jenis::GarisMendatar);
+
+        sesi.urungkan().unwrap();
+        assert_eq!(sesi.papan.to_string(), awal);
+        assert_eq!(sesi.papan.get_jenis((3, 3)), Jenis::Biasa);
+        assert_eq!((sesi.skor, sesi.langkah), (0, 0));
+        assert_eq!(sesi.statistik, Statistik::default());
+
+        sesi.ulangi().unwrap();
+        assert_eq!(sesi.papan.to_string(), akhir);
+        assert_eq!((sesi.skor, sesi.langkah), (skor, 1));
+        assert_eq!(sesi.ulangi(), Err(Galat::RiwayatKosong));
+
+        // giliran baru menghapus giliran yang bisa diulangi
+        sesi.urungkan().unwrap();
+        sesi.persediaan.tambah(Penguat::Palu, 1);
+        sesi.pakai_palu((6, 6)).unwrap();
+        assert_eq!(sesi.ulangi(), Err(Galat::RiwayatKosong));
+        sesi.urungkan().unwrap();
+        assert_eq!(sesi.persediaan.jumlah(Penguat::Palu), 1);
+    }
+
+    #[test]
+    fn sesi_riwayat_batas() {
+        let mut sesi = Sesi::new(papan_bisa_main(7)).dengan_riwayat(2);
+        sesi.persediaan.tambah(Penguat::TukarBebas, 3);
+        for _ in 0..3 {
+            sesi.pakai_tukar_bebas((0, 0), (0, 1)).unwrap();
         }
+        assert_eq!(sesi.riwayat.as_ref().unwrap().bisa_diurungkan(), 2);
+        sesi.urungkan().unwrap();
+        sesi.urungkan().unwrap();
+        assert_eq!(sesi.urungkan(), Err(Galat::RiwayatKosong));
+        assert_eq!(sesi.persediaan.jumlah(Penguat::TukarBebas), 2);
+        assert_eq!(sesi.riwayat.as_ref().unwrap().bisa_diulangi(), 2);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn papan_get_warna() {
-        let papan = Papan::new(5, 3);
-        let warna = papan.isi[0][0].warna.borrow().clone();
-        assert_eq!(papan.get_warna((0, 0)), warna);
+    fn sesi_serde() {
+        let papan: Papan = "R J R\nK R- .\nJ #2 K".parse().unwrap();
+        let mut sesi = Sesi::new(papan).dengan_riwayat(5);
+        sesi.sasaran = Some(Sasaran::Atau(vec![Sasaran::Skor(100), Sasaran::RemukanWarna(Warna::Biru, 3)]));
+        sesi.persediaan.tambah(Penguat::Palu, 1);
+
+        let json = serde_json::to_value(&sesi).unwrap();
+        assert_eq!(json["papan"]["isi"][1][1], serde_json::json!({ "warna": "Merah", "jenis": "GarisMendatar", "id": 4, "bom": 0 }));
+        assert_eq!(json["papan"]["petak"][1][2], "Kosong");
+        assert_eq!(json["papan"]["petak"][2][1], serde_json::json!({ "Penghalang": 2 }));
+        assert_eq!(json["papan"]["jeli"][0], serde_json::json!([0, 0, 0]));
+        assert_eq!(json["sasaran"], serde_json::json!({ "Atau": [{ "Skor": 100 }, { "RemukanWarna": ["Biru", 3] }] }));
+        assert!(json.get("riwayat").is_none());
+
+        let dimuat: Sesi = serde_json::from_value(json).unwrap();
+        assert_eq!(format!("{:#}", dimuat.papan), format!("{:#}", sesi.papan));
+        assert_eq!(dimuat.sasaran, sesi.sasaran);
+        assert_eq!(dimuat.persediaan, sesi.persediaan);
+        assert!(dimuat.riwayat.is_none());
+
+        let hasil = sesi.pakai_palu((0, 0)).unwrap();
+        let json = serde_json::to_value(&hasil).unwrap();
+        assert_eq!(json["kaskade"][0]["remuk"][0], serde_json::json!([[0, 0], "Merah"]));
+        assert_eq!(serde_json::from_value::<HasilGiliran>(json).unwrap(), hasil);
     }
 
+    #[cfg(feature = "serde")]
     #[test]
-    fn papan_set_warna() {
-        let papan = Papan::new(5, 3);
-        papan.set_warna((0, 0), Warna::Merah);
-        assert_eq!(papan.get_warna((0, 0)), Warna::Merah);
+    fn sesi_serde_lama() {
+        // bentuk JSON sebelum ada identitas permen, jeli, kandang, bahan, dan bom
+        let permen = |warna: &str| serde_json::json!({ "warna": warna, "jenis": "Biasa" });
+        let json = serde_json::json!({
+            "papan": {
+                "ukuran": 3,
+                "ragam_warna": 3,
+                "isi": [
+                    [permen("Merah"), permen("Jingga"), permen("Kuning")],
+                    [permen("Kuning"), permen("Merah"), permen("Jingga")],
+                    [permen("Jingga"), permen("Kuning"), permen("Merah")],
+                ],
+                "petak": [["Biasa", "Biasa", "Biasa"], ["Biasa", "Kosong", "Biasa"], ["Biasa", "Biasa", { "Penghalang": 2 }]],
+                "palet": [["Merah", 1], ["Jingga", 1], ["Kuning", 1]],
+                "acak": { "keadaan": 7 },
+            },
+            "skor": 120,
+            "langkah": 3,
+            "batas_langkah": 20,
+            "sisa_waktu_ms": null,
+            "sasaran": { "Skor": 500 },
+            "statistik": { "remuk_warna": [3, 0, 0, 0, 0, 0, 0], "jeli_dibersihkan": 0, "bahan_terkumpul": 0, "kaskade_terbesar": 1, "spesial_dibuat": 0 },
+            "ambang_bintang": [100, 200, 300],
+            "kesulitan": null,
+            "persediaan": { "palu": 1, "tukar_bebas": 0, "kocok": 0, "tambah_langkah": 0 },
+        });
+
+        let sesi: Sesi = serde_json::from_value(json).unwrap();
+        assert_eq!((sesi.skor, sesi.langkah, sesi.sisa_langkah()), (120, 3, Some(17)));
+        assert_eq!(sesi.papan.get_petak((2, 2)), Petak::Penghalang(2));
+        assert_eq!(sesi.papan.sisa_jeli(), 0);
+        assert!(!sesi.papan.terkunci((0, 0)));
+        assert_eq!(sesi.papan.get_bom((0, 0)), 0);
+        assert_eq!((sesi.jadwal_bahan, sesi.jadwal_bom), (None, None));
+        assert!(sesi.bom_meledak.is_empty());
+        let mut id = sesi.papan.semua_id();
+        id.sort();
+        assert_eq!(id, (0..9).collect::<Vec<u32>>());
+        assert_eq!(sesi.papan.permen_baru().get_id(), 9);
     }
 
     #[test]
-    fn papan_tukar() {
-        let papan = Papan::new(3, 3);
-        let warna0 = papan.get_warna((0, 0));
-        papan.tukar((0,0), (0,1));
-        let warna1 = papan.get_warna((0, 1));
-        assert_eq!(warna0, warna1);
+    fn sesi_peristiwa() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7)).dengan_sasaran(Sasaran::Skor(10_000));
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+
+        let _ = sesi.tukar((0, 0), (0, 1));
+        assert_eq!(sesi.kuras_peristiwa(), vec![
+            Peristiwa::TukarDicoba((0, 0), (0, 1)),
+            Peristiwa::TukarDitolak((0, 0), (0, 1), Galat::TidakAdaDempet),
+        ]);
+
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        let peristiwa = sesi.kuras_peristiwa();
+        assert_eq!(peristiwa[0], Peristiwa::TukarDicoba((4, 3), (3, 3)));
+        assert_eq!(peristiwa[1], Peristiwa::TingkatKaskade(1));
+        assert_eq!(peristiwa[2], Peristiwa::DempetDitemukan(hasil.kaskade[0].kelompok[0].clone()));
+        assert_eq!(peristiwa[3], Peristiwa::SpesialDibuat((3, 3), Jenis::GarisMendatar));
+        assert_eq!(peristiwa[4], Peristiwa::PermenJatuh((2, 0), (3, 0)));
+        assert_eq!(hasil.kaskade[0].jatuh.len(), 9);
+        assert_eq!(hasil.kaskade[0].muncul.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), vec![(0, 0), (0, 1), (0, 2)]);
+        let jumlah = |cocok: fn(&Peristiwa) -> bool| peristiwa.iter().filter(|p| cocok(p)).count();
+        assert_eq!(jumlah(|p| matches!(p, Peristiwa::TingkatKaskade(_))), hasil.kaskade.len());
+        assert_eq!(jumlah(|p| matches!(p, Peristiwa::SkorBertambah(_))), hasil.kaskade.len());
+        assert_eq!(jumlah(|p| matches!(p, Peristiwa::JeliDibersihkan(..))), 0);
+        assert_eq!(peristiwa.last(), Some(&Peristiwa::KemajuanSasaran(sesi.kemajuan())));
+
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        sesi.sasaran = Some(Sasaran::Skor(1));
+        sesi.pakai_kocok().unwrap_err();
+        assert_eq!(sesi.kuras_peristiwa(), vec![]);
+        sesi.sasaran = None;
+        sesi.batas_langkah = Some(sesi.langkah);
+        sesi.persediaan.tambah(Penguat::TambahLangkah, 1);
+        sesi.pakai_tambah_langkah().unwrap();
+        assert!(sesi.kuras_peristiwa().is_empty());
+        sesi.pakai_kocok().unwrap();
+        assert_eq!(sesi.kuras_peristiwa()[0], Peristiwa::Dikocok);
     }
 
     #[test]
-    fn papan_remukan() {
-        let papan = Papan::new(3, 3);
-        let warna0 = papan.get_warna((0, 0));
-        let warna1 = papan.get_warna((1, 0));
-        papan.remukan((2, 0));
-        assert_eq!(papan.get_warna((2, 0)), warna1);
-        assert_eq!(papan.get_warna((1, 0)), warna0);
+    fn sesi_fase_animasi() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7));
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        let semua_id = |papan: &Papan| {
+            let mut id: Vec<u32> = (0..7).flat_map(|y| (0..7).map(move |x| (y, x))).map(|pos| papan.get_id(pos)).collect();
+            id.sort();
+            id.dedup();
+            id.len()
+        };
+        assert_eq!(semua_id(&sesi.papan), 49);
+        let id_jatuh = sesi.papan.get_id((2, 0));
+        let id_tukar = sesi.papan.get_id((4, 3));
+
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        let fase = hasil.fase();
+        assert_eq!(fase.len(), 1 + 3 * hasil.kaskade.len());
+        assert_eq!(fase[0], Fase::Tukar(vec![
+            Gerak { id: id_tukar, dari: (4, 3), ke: (3, 3) },
+            Gerak { id: sesi.papan.get_id((4, 3)), dari: (3, 3), ke: (4, 3) },
+        ]));
+        assert!(matches!(fase[1], Fase::Remuk(ref gerak) if gerak.len() == 3));
+        assert!(fase[2].gerak().contains(&Gerak { id: id_jatuh, dari: (2, 0), ke: (3, 0) }));
+        let masuk: Vec<_> = fase[3].gerak().iter().map(|gerak| (gerak.dari, gerak.ke)).collect();
+        assert_eq!(masuk, vec![((-1, 0), (0, 0)), ((-1, 1), (0, 1)), ((-1, 2), (0, 2))]);
+
+        // permen yang jatuh tetap membawa identitasnya, permen baru mendapat identitas baru
+        let ksk = &hasil.kaskade[0];
+        if hasil.kaskade.len() == 1 {
+            assert_eq!(sesi.papan.get_id((3, 0)), id_jatuh);
+            for gerak in ksk.fase[2].gerak() {
+                assert_eq!(sesi.papan.get_id(gerak.ke), gerak.id);
+                assert!(gerak.id >= 49);
+            }
+        }
+        assert_eq!(semua_id(&sesi.papan), 49);
+
+        // permen baru di kolom yang sama berbaris di atas papan
+        let papan: Papan = "R J K\nR K J\nR J K".parse().unwrap();
+        let kaskade = papan.remukan_kaskade(&[]);
+        let masuk: Vec<_> = kaskade[0].fase[2].gerak().iter().map(|gerak| (gerak.dari, gerak.ke)).collect();
+        assert_eq!(masuk, vec![((-3, 0), (0, 0)), ((-2, 0), (1, 0)), ((-1, 0), (2, 0))]);
     }
 
     #[test]
-    fn papan_cek_horizontal() {
-        let papan = Papan::new(7, 3);
-        for i in 0..papan.ukuran {
-            papan.set_warna((0, i), Warna::Merah);
+    fn sesi_sasaran_jeli() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7)).dengan_sasaran(Sasaran::BersihkanJeli(2));
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
         }
-        let dempet = papan.cek_horizontal((0, 0), 3);
-        assert_eq!(dempet.len(), 7);
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        sesi.papan.set_jeli((3, 0), 1);
+        sesi.papan.set_jeli((3, 1), 1);
 
-        papan.set_warna((0, 3), Warna::Biru);
-        let dempet = papan.cek_horizontal((0, 0), 3);
-        assert_eq!(dempet.len(), 3);
-        let dempet = papan.cek_horizontal((0, 6), 3);
-        assert_eq!(dempet.len(), 3);
+        sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert_eq!(sesi.statistik.jeli_dibersihkan, 2);
+        assert!(sesi.selesai());
+        assert!(sesi.ringkasan().sasaran_tercapai);
+        let peristiwa = sesi.kuras_peristiwa();
+        assert!(peristiwa.contains(&Peristiwa::JeliDibersihkan((3, 0), 0)));
+        assert!(peristiwa.contains(&Peristiwa::JeliDibersihkan((3, 1), 0)));
     }
 
     #[test]
-    fn papan_cek_vertikal() {
-        let papan = Papan::new(7, 3);
-        for i in 0..papan.ukuran {
-            papan.set_warna((i, 0), Warna::Merah);
+    fn papan_cokelat() {
+        let papan = papan_tanpa_dempet(7);
+        for i in 0..3 {
+            papan.set_warna((3, i), Warna::Merah);
         }
-        let dempet = papan.cek_vertikal((0, 0), 3);
-        assert_eq!(dempet.len(), 7);
+        papan.set_petak((2, 0), Petak::Cokelat);
+        papan.set_petak((6, 6), Petak::Cokelat);
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert_eq!(kaskade[0].cokelat_hancur, vec![(2, 0)]);
+        assert!(papan.punya_permen((2, 0)));
+        assert_eq!(papan.kode()[2 + (6 * 7 + 6) * KODE_PER_PETAK], 3);
 
-        papan.set_warna((3, 0), Warna::Biru);
-        let dempet = papan.cek_vertikal((0, 0), 3);
-        assert_eq!(dempet.len(), 3);
-        let dempet = papan.cek_vertikal((6, 0), 3);
-        assert_eq!(dempet.len(), 3);
+        // cokelat yang dikelilingi petak tanpa permen tidak bisa menyebar
+        papan.set_petak((5, 6), Petak::Kosong);
+        papan.set_petak((6, 5), Petak::Penghalang(1));
+        assert_eq!(papan.sebarkan_cokelat(), None);
     }
 
     #[test]
-    fn papan_cek_dempet() {
-        let papan = Papan::new(7, 3);
-        for i in 0..papan.ukuran {
-            papan.set_warna((i, 0), Warna::Merah);
-            papan.set_warna((0, i), Warna::Merah);
+    fn papan_kunci() {
+        let papan = papan_tanpa_dempet(7);
+        papan.set_kunci((4, 0), true);
+        let (warna_atas, warna_terkunci) = (papan.get_warna((3, 0)), papan.get_warna((4, 0)));
+
+        // permen terkunci tidak bisa ditukar dan menahan permen di atasnya
+        papan.tukar((4, 0), (4, 1));
+        assert_eq!(papan.get_warna((4, 0)), warna_terkunci);
+        assert!(!papan.bisa_ditukar((4, 0)));
+        assert!(!papan.mungkin_ditukar((4, 0)));
+        papan.remukan((5, 0));
+        assert_eq!(papan.get_warna((4, 0)), warna_terkunci);
+        assert_eq!(papan.get_warna((3, 0)), warna_atas);
+
+        // permen terkunci boleh ikut dempet, kandangnya terbuka tanpa permennya remuk
+        for i in 0..3 {
+            papan.set_warna((4, i), Warna::Merah);
         }
-        let dempet = papan.cek_dempet((0, 0), 3);
-        assert_eq!(dempet.len(), 13);
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert_eq!(kaskade[0].kunci_dibuka, vec![(4, 0)]);
+        assert!(!kaskade[0].remuk.iter().any(|(pos, _)| *pos == (4, 0)));
+        assert!(!papan.terkunci((4, 0)));
     }
 
     #[test]
-    fn papan_mungkin_ditukar() {
-        let papan = Papan::new(5, 3);
-        papan.set_warna((0, 0), Warna::Merah);
-        papan.set_warna((1, 1), Warna::Merah);
-        papan.set_warna((1, 2), Warna::Merah);
-        assert!(papan.mungkin_ditukar((0, 0)));
+    fn papan_bahan() {
+        // bahan tidak pernah dempet walaupun warnanya sama
+        let papan: Papan = "& & &\nK R K\nJ K J".parse().unwrap();
+        assert!(papan.semua_dempet().is_empty());
+        assert_eq!(papan.jumlah_bahan(), 3);
+        assert_eq!(format!("{:#}", papan).lines().next(), Some("& & & "));
+
+        // bahan jatuh ke petak keluar lalu terkumpul
+        let mut papan: Papan = "J K J\nK & K\nR R R-".parse().unwrap();
+        papan.keluar = vec![1];
+        assert_eq!(papan.kode()[2 + 7 * KODE_PER_PETAK + 5], TANDA_KELUAR);
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert!(kaskade[0].remuk.iter().all(|(pos, _)| pos.0 == 2));
+        assert_eq!(kaskade[0].bahan_terkumpul, vec![(2, 1)]);
+        assert_eq!(kaskade[0].fase.len(), 6);
+        assert_eq!(papan.jumlah_bahan(), 0);
     }
 
     #[test]
-    fn papan_cek_kemungkinan() {
-        let papan = Papan::new(5, 3);
-        papan.set_warna((0, 0), Warna::Merah);
-        papan.set_warna((1, 1), Warna::Merah);
-        papan.set_warna((1, 2), Warna::Merah);
-        assert!(papan.cek_kemungkinan().len() >= 1);
+    fn sesi_bahan_terkumpul() {
+        // bahan yang terkumpul tidak menambah tingkat kaskade yang kosong
+        let mut papan: Papan = "J K J\nK & R\nR R H".parse().unwrap();
+        papan.keluar = vec![1];
+        let mut sesi = Sesi::berwaktu(papan, 10_000);
+        let hasil = sesi.tukar((1, 2), (2, 2)).unwrap();
+        assert_eq!(hasil.kaskade[0].bahan_terkumpul, vec![(2, 1)]);
+        assert!(hasil.kaskade.iter().all(|ksk| !ksk.kelompok.is_empty()));
+        assert_eq!(sesi.statistik.kaskade_terbesar, hasil.kaskade.len() as u32);
+        assert_eq!(sesi.statistik.bahan_terkumpul, 1);
     }
 
     #[test]
-    fn papan_cek_kemungkinan_str() {
-        let papan = Papan::new(5, 3);
-        let mut result = String::new();
-        let vektor_kemungkinan = papan.cek_kemungkinan();
-        for pos in vektor_kemungkinan {
-            result.push_str(&format!("{}:{} ", pos.0, pos.1))
+    fn sesi_jadwal_bahan() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7)).dengan_sasaran(Sasaran::TurunkanBahan(1));
+        sesi.jadwal_bahan = Some(JadwalBahan { setiap: 1, maks: 1 });
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
         }
-        assert_eq!(papan.cek_kemungkinan_str(), result);
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert_eq!(sesi.papan.bahan_tertunda.get(), 1);
+
+        // bahan muncul pada isian berikutnya, jadwal berhenti selama papan sudah penuh bahan
+        sesi.papan.set_warna((6, 4), Warna::Biru);
+        sesi.papan.set_warna((6, 5), Warna::Biru);
+        sesi.papan.set_warna((5, 6), Warna::Biru);
+        sesi.tukar((5, 6), (6, 6)).unwrap();
+        assert_eq!(sesi.papan.jumlah_bahan(), 1);
+        assert_eq!(sesi.papan.bahan_tertunda.get(), 0);
+        assert!(!sesi.sasaran_tercapai());
+    }
+
+    #[test]
+    fn papan_bom() {
+        // bom ikut jatuh bersama sisa gilirannya, bom yang remuk dijinakkan
+        let papan: Papan = "J!4 K J\nK J K\nR R!2 R".parse().unwrap();
+        *papan.acak.borrow_mut() = Acak::new(49);
+        assert_eq!(papan.jumlah_bom(), 2);
+        assert_eq!(papan.kode()[2 + 7 * KODE_PER_PETAK + 6], 2);
+        let id = papan.get_id((0, 0));
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert_eq!(kaskade[0].bom_dijinakkan, vec![(2, 1)]);
+        let pos = (0..3).flat_map(|y| (0..3).map(move |x| (y, x))).find(|pos| papan.get_id(*pos) == id).unwrap();
+        assert_eq!(papan.get_bom(pos), 4);
+        assert_eq!(papan.jumlah_bom(), 1);
+
+        // bom ikut berpindah ketika ditukar dan tidak pernah tertutup penghalang atau cokelat
+        papan.tukar(pos, (pos.0, (pos.1 + 1) % 3));
+        assert_eq!(papan.get_bom((pos.0, (pos.1 + 1) % 3)), 4);
+        let papan: Papan = "R!3 % #\nJ K R\nK R J".parse().unwrap();
+        assert_eq!(papan.sebarkan_cokelat().map(|(_, ditelan)| ditelan), Some((1, 1)));
+        assert_eq!(papan.munculkan_penghalang(1).map(|pos| papan.get_bom(pos)), Some(0));
+        assert_eq!(papan.get_bom((0, 0)), 3);
+    }
+
+    #[test]
+    fn sesi_bom() {
+        let buat_sesi = || {
+            let sesi = Sesi::new(papan_tanpa_dempet(7)).dengan_riwayat(5);
+            for i in 0..3 {
+                sesi.papan.set_warna((3, i), Warna::Merah);
+            }
+            sesi.papan.set_warna((4, 3), Warna::Merah);
+            sesi.papan.set_bom((6, 6), 1);
+            sesi
+        };
+
+        // sisa giliran bom habis di akhir langkah, permainan kalah
+        let mut sesi = buat_sesi().dengan_batas_langkah(10).dengan_ambang_bintang([1, 2, 3]);
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert_eq!(hasil.bom_meledak, vec![(6, 6)]);
+        assert!(sesi.selesai());
+        assert_eq!(sesi.bintang(), 0);
+        let peristiwa = sesi.kuras_peristiwa();
+        assert!(peristiwa.contains(&Peristiwa::BomMeledak((6, 6))));
+        assert!(matches!(peristiwa.last(), Some(Peristiwa::PermainanSelesai(_))));
+        assert_eq!(sesi.pakai_tambah_langkah(), Err(Galat::PermainanSelesai));
+        sesi.urungkan().unwrap();
+        assert!(sesi.bom_meledak.is_empty());
+        assert_eq!(sesi.papan.get_bom((6, 6)), 1);
+
+        // bom dijadwalkan muncul setiap langkah sampai batasnya
+        let mut sesi = buat_sesi();
+        sesi.papan.set_bom((6, 6), 5);
+        sesi.jadwal_bom = Some(JadwalBom { setiap: 1, hitungan: 3, maks: 2 });
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert!(hasil.bom_meledak.is_empty());
+        assert_eq!(sesi.papan.get_bom((6, 6)), 4);
+        assert_eq!(*sesi.papan.bom_tertunda.borrow(), vec![3]);
+        assert!(!sesi.selesai());
+    }
+
+    #[test]
+    fn papan_pelangi() {
+        let kelompok = |teks: &str| {
+            let papan: Papan = teks.parse().unwrap();
+            papan.semua_kelompok().into_iter().map(|klp| (klp.warna, klp.dempet.vektor)).collect::<Vec<_>>()
+        };
+
+        // pelangi memanjangkan deretan di kedua sisinya dan menyambung deretan berwarna sama
+        let papan: Papan = "R $ R J\nJ K J K\nK J K J\nJ K J K".parse().unwrap();
+        assert_eq!(papan.cek_horizontal((0, 1), 3), vec![(0, 0), (0, 1), (0, 2)]);
+        assert_eq!(kelompok("R R $ R R\nJ K J K J\nK J K J K\nJ K J K J\nK J K J K"), vec![
+            (Warna::Merah, vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]),
+        ]);
+
+        // pelangi di antara dua warna berbeda masuk ke kedua kelompok, kelompoknya tidak digabung
+        assert_eq!(kelompok("R R $ J J\nJ K H K H\nK J K J K\nJ K J K J\nK J K J K"), vec![
+            (Warna::Merah, vec![(0, 0), (0, 1), (0, 2)]),
+            (Warna::Jingga, vec![(0, 2), (0, 3), (0, 4)]),
+        ]);
+        assert!(kelompok("R $ J\nJ K H\nK J K").is_empty());
+
+        // deretan tanpa warna tidak dempet, dua pelangi cukup dengan satu warna
+        assert!(kelompok("$ $ $\nJ K H\nK J K").is_empty());
+        assert_eq!(kelompok("$ $ R\nJ K H\nK J K"), vec![(Warna::Merah, vec![(0, 0), (0, 1), (0, 2)])]);
+
+        // pelangi remuk tanpa warna dan tidak dipicu seperti permen spesial
+        let papan: Papan = "R R $ J J\nJ K H K H\nK J K J K\nJ K J K J\nK J K J K".parse().unwrap();
+        *papan.acak.borrow_mut() = Acak::new(50);
+        let kaskade = papan.remukan_kaskade(&[]);
+        assert_eq!(kaskade[0].kelompok.len(), 2);
+        assert_eq!(kaskade[0].jumlah_permen(), 5);
+        assert_eq!(kaskade[0].pelangi_remuk, vec![(0, 2)]);
+        assert!(!kaskade[0].remuk.iter().any(|(pos, _)| *pos == (0, 2)));
+        assert!(kaskade[0].spesial_dipicu.is_empty());
+        let papan: Papan = "$ R J\nJ K H\nK J K".parse().unwrap();
+        assert_eq!(papan.to_string().lines().next(), Some("🌈 🟥 🟧 "));
+        assert_eq!(format!("{:#}", papan).lines().next(), Some("$ R J "));
+    }
+
+    #[test]
+    fn papan_pelangi_kemungkinan() {
+        // menaikkan permen merah di (1, 2) hanya menghasilkan dempet karena ada pelangi
+        let papan: Papan = "R $ K J\nJ K R H\nK J K J\nJ K J R".parse().unwrap();
+        assert!(papan.semua_dempet().is_empty());
+        assert!(papan.cek_kemungkinan().contains(&(1, 2)));
+        papan.set_jenis((0, 1), Jenis::Biasa);
+        papan.set_warna((0, 1), Warna::Hijau);
+        assert!(!papan.cek_kemungkinan().contains(&(1, 2)));
+    }
+
+    #[test]
+    fn sesi_pelangi() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7));
+        *sesi.papan.acak.borrow_mut() = Acak::new(50);
+        sesi.papan.set_warna((3, 0), Warna::Merah);
+        sesi.papan.set_warna((3, 1), Warna::Merah);
+        sesi.papan.set_jenis((4, 2), Jenis::Pelangi);
+        assert!(sesi.papan.cek_kemungkinan().contains(&(4, 2)));
+
+        let hasil = sesi.tukar((4, 2), (3, 2)).unwrap();
+        assert_eq!(hasil.kaskade[0].kelompok[0].warna, Warna::Merah);
+        assert!(hasil.kaskade[0].remuk.iter().all(|(_, warna)| *warna == Warna::Merah));
+        assert_eq!(hasil.kaskade[0].pelangi_remuk, vec![(3, 2)]);
+        assert_eq!(hasil.kaskade[0].jumlah_permen(), 3);
+        assert_eq!(hasil.kaskade.len(), 1);
+        assert_eq!(sesi.statistik.remuk_warna[Warna::Merah.indeks()], 2);
+
+        // pelangi yang dipalu tidak menambah hitungan warna apa pun
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7));
+        sesi.persediaan.tambah(Penguat::Palu, 1);
+        sesi.papan.set_jenis((3, 3), Jenis::Pelangi);
+        let hasil = sesi.pakai_palu((3, 3)).unwrap();
+        assert_eq!(hasil.kaskade[0].pelangi_remuk, vec![(3, 3)]);
+        assert!(hasil.kaskade[0].remuk.is_empty());
+        assert_eq!(sesi.statistik.remuk_warna.iter().sum::<u32>(), 0);
+    }
+
+    #[test]
+    fn sesi_cokelat_menyebar() {
+        let buat_sesi = || {
+            let sesi = Sesi::new(papan_tanpa_dempet(7));
+            *sesi.papan.acak.borrow_mut() = Acak::new(46);
+            for i in 0..3 {
+                sesi.papan.set_warna((3, i), Warna::Merah);
+            }
+            sesi.papan.set_warna((4, 3), Warna::Merah);
+            sesi.papan.set_petak((6, 6), Petak::Cokelat);
+            sesi
+        };
+
+        // tidak ada cokelat yang hancur, cokelat menelan satu permen di sebelahnya
+        let mut sesi = buat_sesi();
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        let (asal, ditelan) = hasil.cokelat_menyebar.unwrap();
+        assert_eq!(asal, (6, 6));
+        assert!(ditelan == (5, 6) || ditelan == (6, 5));
+        assert_eq!(sesi.papan.get_petak(ditelan), Petak::Cokelat);
+        assert!(sesi.kuras_peristiwa().contains(&Peristiwa::CokelatMenyebar(asal, ditelan)));
+
+        // cokelat yang hancur selama giliran mencegah cokelat lain menyebar
+        let mut sesi = buat_sesi();
+        sesi.papan.set_petak((2, 1), Petak::Cokelat);
+        let hasil = sesi.tukar((4, 3), (3, 3)).unwrap();
+        assert_eq!(hasil.cokelat_menyebar, None);
+        assert!(sesi.kuras_peristiwa().contains(&Peristiwa::CokelatHancur((2, 1))));
+    }
+
+    #[test]
+    fn sesi_peristiwa_selesai() {
+        let mut sesi = Sesi::new(papan_tanpa_dempet(7)).dengan_batas_langkah(1);
+        for i in 0..3 {
+            sesi.papan.set_warna((3, i), Warna::Merah);
+        }
+        sesi.papan.set_warna((4, 3), Warna::Merah);
+        sesi.tukar((4, 3), (3, 3)).unwrap();
+        let peristiwa = sesi.kuras_peristiwa();
+        assert_eq!(peristiwa.last(), Some(&Peristiwa::PermainanSelesai(sesi.ringkasan())));
+    }
+
+    #[test]
+    fn sesi_peristiwa_waktu_habis() {
+        let mut sesi = Sesi::berwaktu(papan_bisa_main(5), 500);
+        sesi.tick(300);
+        assert!(sesi.kuras_peristiwa().is_empty());
+        sesi.tick(300);
+        assert_eq!(sesi.kuras_peristiwa(), vec![Peristiwa::PermainanSelesai(sesi.ringkasan())]);
+        sesi.tick(300);
+        assert!(sesi.kuras_peristiwa().is_empty());
+
+        // permainan yang sudah selesai karena sebab lain tidak dicatat lagi
+        let mut sesi = Sesi::berwaktu(papan_tanpa_dempet(5), 500).dengan_sasaran(Sasaran::Skor(0));
+        sesi.tick(500);
+        assert!(sesi.kuras_peristiwa().is_empty());
+    }
+
+    #[test]
+    fn sesi_waktu_habis() {
+        let mut sesi = Sesi::berwaktu(papan_tanpa_dempet(5), 500);
+        sesi.tick(500);
+        assert_eq!(sesi.tukar((0, 0), (0, 1)), Err(Galat::PermainanSelesai));
+    }
+
+    #[test]
+    fn pilihan_buat_sesi() {
+        let pilihan = Pilihan { ukuran: 6, ragam_warna: 4, benih: Some(99), ..Pilihan::default() };
+        let sesi = pilihan.buat_sesi();
+        assert_eq!(sesi.papan.ukuran, 6);
+        assert_eq!(sesi.papan.kode(), pilihan.buat_sesi().papan.kode());
+        assert!(sesi.papan.isi.iter().flatten().all(|permen| permen.get_warna().indeks() < 4));
+        assert!(sesi.papan.semua_dempet().is_empty());
+        let mut id = sesi.papan.semua_id();
+        id.sort();
+        assert_eq!(id, (0..36).collect::<Vec<u32>>());
+        assert!(sesi.riwayat.is_none());
+
+        let lain = Pilihan { benih: Some(100), ..pilihan.clone() };
+        assert_ne!(sesi.papan.kode(), lain.buat_sesi().papan.kode());
+    }
+
+    #[test]
+    fn pilihan_mode() {
+        let buat = |mode| Pilihan { mode, batas_riwayat: 5, ..Pilihan::default() }.buat_sesi();
+        assert_eq!(buat(Mode::Langkah(10)).sisa_langkah(), Some(10));
+        assert_eq!(buat(Mode::Waktu(3_000)).sisa_waktu_ms, Some(3_000));
+        assert_eq!(buat(Mode::TanpaAkhir).kesulitan, Some(0));
+        let bebas = buat(Mode::Bebas);
+        assert_eq!((bebas.sisa_langkah(), bebas.sisa_waktu_ms, bebas.kesulitan), (None, None, None));
+        assert_eq!(bebas.riwayat.map(|riwayat| riwayat.batas), Some(5));
+
+        let warna = Pilihan { ragam_warna: 1, ..Pilihan::default() }.buat_sesi();
+        assert_eq!(warna.papan.palet.len(), 3);
     }
 }
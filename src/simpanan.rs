@@ -0,0 +1,590 @@
+//! Simpanan permainan: seluruh keadaan Sesi ditulis sebagai string pendek yang aman
+//! dipakai di URL, berbentuk `versi.isi` dengan isi berupa byte yang dikodekan base64url.
+//!
+//! Isi simpanan mencakup papan (petak, warna dan jenis permen, jeli, kandang, bom, serta petak keluar, bahan,
+//! dan bom yang tertunda), palet, keadaan sumber acak, skor, langkah, batas, sasaran, statistik, persediaan
+//! penguat, jadwal bahan dan bom, serta bom yang sudah meledak, sehingga permainan yang
+//! dimuat ulang berlanjut persis sama termasuk permen yang muncul berikutnya.
+//! Riwayat urungkan tidak ikut disimpan.
+//!
+//! Setiap perubahan susunan isi menaikkan VERSI_SIMPANAN, dan simpanan versi lama tetap dibaca:
+//!
+//! - versi 1: susunan awal, 4 byte per petak
+//! - versi 2: byte kelima per petak untuk lapisan jeli
+//! - versi 3: byte keenam per petak untuk kandang permen
+//! - versi 4: byte keenam menjadi byte tanda yang juga menandai petak keluar,
+//!   ditambah jumlah bahan tertunda setelah isi papan dan jadwal bahan di akhir
+//! - versi 5: byte ketujuh per petak untuk sisa giliran bom, ditambah bom tertunda setelah bahan tertunda,
+//!   lalu jadwal bom dan bom yang meledak setelah jadwal bahan
+//! - versi 6: identitas berikutnya dan identitas permen setiap petak setelah bom tertunda,
+//!   simpanan lama memberi identitas berurutan baris demi baris
+
+use std::cell::RefCell;
+
+use crate::permenremuk::*;
+
+/// Versi susunan simpanan yang ditulis oleh Sesi::simpanan
+pub const VERSI_SIMPANAN: u32 = 6;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn ke_base64(bytes: &[u8]) -> String {
+    let mut hasil = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for potongan in bytes.chunks(3) {
+        let mut angka = 0u32;
+        for (i, byte) in potongan.iter().enumerate() {
+            angka |= (*byte as u32) << (16 - 8 * i);
+        }
+        for i in 0..=potongan.len() {
+            hasil.push(BASE64[(angka >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    hasil
+}
+
+fn dari_base64(teks: &str) -> Result<Vec<u8>, Galat> {
+    let galat = || Galat::FormatTidakSah("isi simpanan bukan base64url".to_string());
+    let mut hasil = Vec::with_capacity(teks.len() * 3 / 4);
+    for potongan in teks.as_bytes().chunks(4) {
+        if potongan.len() == 1 {
+            return Err(galat());
+        }
+        let mut angka = 0u32;
+        for (i, huruf) in potongan.iter().enumerate() {
+            let nilai = BASE64.iter().position(|b| b == huruf).ok_or_else(galat)?;
+            angka |= (nilai as u32) << (18 - 6 * i);
+        }
+        for i in 0..potongan.len() - 1 {
+            hasil.push((angka >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(hasil)
+}
+
+// Menulis angka sebagai varint (LEB128) agar angka kecil cukup satu byte
+#[derive(Default)]
+struct Penulis {
+    bytes: Vec<u8>,
+}
+
+impl Penulis {
+    fn byte(&mut self, byte: u8) {
+        self.bytes.push(byte);
+    }
+
+    fn angka(&mut self, mut angka: u64) {
+        while angka >= 0x80 {
+            self.bytes.push(angka as u8 | 0x80);
+            angka >>= 7;
+        }
+        self.bytes.push(angka as u8);
+    }
+
+    fn pilihan(&mut self, angka: Option<u64>) {
+        match angka {
+            Some(angka) => {
+                self.byte(1);
+                self.angka(angka);
+            }
+            None => self.byte(0),
+        }
+    }
+
+    fn sasaran(&mut self, sasaran: &Sasaran) {
+        match sasaran {
+            Sasaran::Skor(n) => {
+                self.byte(0);
+                self.angka(*n as u64);
+            }
+            Sasaran::RemukanWarna(warna, n) => {
+                self.byte(1);
+                self.byte(warna.indeks() as u8);
+                self.angka(*n as u64);
+            }
+            Sasaran::BersihkanJeli(n) => {
+                self.byte(2);
+                self.angka(*n as u64);
+            }
+            Sasaran::TurunkanBahan(n) => {
+                self.byte(3);
+                self.angka(*n as u64);
+            }
+            Sasaran::Dan(semua) | Sasaran::Atau(semua) => {
+                self.byte(if matches!(sasaran, Sasaran::Dan(_)) { 4 } else { 5 });
+                self.angka(semua.len() as u64);
+                for sasaran in semua {
+                    self.sasaran(sasaran);
+                }
+            }
+        }
+    }
+}
+
+struct Pembaca<'a> {
+    bytes: &'a [u8],
+}
+
+impl Pembaca<'_> {
+    fn byte(&mut self) -> Result<u8, Galat> {
+        let (byte, sisa) = self.bytes.split_first().ok_or_else(|| tidak_sah("simpanan terpotong"))?;
+        self.bytes = sisa;
+        Ok(*byte)
+    }
+
+    fn angka(&mut self) -> Result<u64, Galat> {
+        let mut angka = 0u64;
+        for geser in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            angka |= ((byte & 0x7f) as u64) << geser;
+            if byte < 0x80 {
+                return Ok(angka);
+            }
+        }
+        Err(tidak_sah("angka terlalu besar"))
+    }
+
+    fn angka_u32(&mut self) -> Result<u32, Galat> {
+        self.angka()?.try_into().map_err(|_| tidak_sah("angka terlalu besar"))
+    }
+
+    fn pilihan(&mut self) -> Result<Option<u64>, Galat> {
+        match self.byte()? {
+            0 => Ok(None),
+            1 => Ok(Some(self.angka()?)),
+            _ => Err(tidak_sah("tanda pilihan tidak dikenal")),
+        }
+    }
+
+    fn warna(&mut self) -> Result<Warna, Galat> {
+        let indeks = self.byte()? as usize;
+        SEMUA_WARNA.get(indeks).cloned().ok_or_else(|| tidak_sah("warna tidak dikenal"))
+    }
+
+    fn sasaran(&mut self, kedalaman: usize) -> Result<Sasaran, Galat> {
+        if kedalaman > 8 {
+            return Err(tidak_sah("sasaran bersarang terlalu dalam"));
+        }
+        let sasaran = match self.byte()? {
+            0 => Sasaran::Skor(self.angka_u32()?),
+            1 => Sasaran::RemukanWarna(self.warna()?, self.angka_u32()?),
+            2 => Sasaran::BersihkanJeli(self.angka_u32()?),
+            3 => Sasaran::TurunkanBahan(self.angka_u32()?),
+            tanda @ (4 | 5) => {
+                let jumlah = self.angka()?;
+                let semua = (0..jumlah)
+                    .map(|_| self.sasaran(kedalaman + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if tanda == 4 { Sasaran::Dan(semua) } else { Sasaran::Atau(semua) }
+            }
+            _ => return Err(tidak_sah("sasaran tidak dikenal")),
+        };
+        Ok(sasaran)
+    }
+}
+
+fn tidak_sah(pesan: &str) -> Galat {
+    Galat::FormatTidakSah(pesan.to_string())
+}
+
+fn tulis_papan(penulis: &mut Penulis, papan: &Papan) {
+    penulis.angka(papan.ukuran as u64);
+    penulis.angka(papan.ragam_warna as u64);
+    penulis.angka(papan.palet.len() as u64);
+    for (warna, bobot) in papan.palet.iter() {
+        penulis.byte(warna.indeks() as u8);
+        penulis.angka(*bobot as u64);
+    }
+    penulis.angka(papan.acak.borrow().keadaan());
+    penulis.bytes.extend_from_slice(&papan.kode()[2..]);
+    penulis.angka(papan.bahan_tertunda.get() as u64);
+    let bom_tertunda = papan.bom_tertunda.borrow();
+    penulis.angka(bom_tertunda.len() as u64);
+    for sisa in bom_tertunda.iter() {
+        penulis.byte(*sisa);
+    }
+    tulis_id(penulis, papan);
+}
+
+fn tulis_id(penulis: &mut Penulis, papan: &Papan) {
+    penulis.angka(papan.id_berikutnya.get() as u64);
+    for id in papan.semua_id() {
+        penulis.angka(id as u64);
+    }
+}
+
+fn baca_papan(pembaca: &mut Pembaca, versi: u32) -> Result<Papan, Galat> {
+    let ukuran = pembaca.angka()? as usize;
+    if !(PAPAN_MIN..=PAPAN_MAX).contains(&ukuran) {
+        return Err(tidak_sah("ukuran papan tidak sah"));
+    }
+    let ragam_warna = pembaca.angka()? as usize;
+    let jumlah_warna = pembaca.angka()?;
+    let mut palet = vec![];
+    for _ in 0..jumlah_warna.min(SEMUA_WARNA.len() as u64 + 1) {
+        palet.push((pembaca.warna()?, pembaca.angka_u32()?));
+    }
+    if palet.is_empty() || palet.len() > SEMUA_WARNA.len() || palet.iter().any(|(_, bobot)| *bobot == 0) {
+        return Err(tidak_sah("palet tidak sah"));
+    }
+
+    // Isian papan ditimpa di bawah, keadaan acak ditentukan setelah papan dibuat
+    let mut papan = Papan::dengan_benih(ukuran, ragam_warna, 0);
+    papan.palet = palet;
+    papan.acak = RefCell::new(Acak::new(pembaca.angka()?));
+    for y in 0..ukuran {
+        for x in 0..ukuran {
+            let (kode, nilai) = (pembaca.byte()?, pembaca.byte()?);
+            let petak = Petak::dari_kode(kode, nilai).ok_or_else(|| tidak_sah("petak tidak dikenal"))?;
+            let (warna, jenis) = (pembaca.byte()?, pembaca.byte()?);
+            if petak == Petak::Biasa {
+                let warna = SEMUA_WARNA.get(warna as usize).ok_or_else(|| tidak_sah("warna tidak dikenal"))?;
+                let jenis = Jenis::dari_kode(jenis).ok_or_else(|| tidak_sah("jenis tidak dikenal"))?;
+                papan.set_warna((y, x), warna.clone());
+                papan.set_jenis((y, x), jenis);
+            }
+            if versi >= 2 {
+                let jeli = pembaca.byte()?;
+                if jeli > JELI_MAKS || (jeli > 0 && petak == Petak::Kosong) {
+                    return Err(tidak_sah("jeli tidak sah"));
+                }
+                papan.set_jeli((y, x), jeli);
+            }
+            if versi >= 3 {
+                let tanda = pembaca.byte()?;
+                let boleh = if versi >= 4 { TANDA_KUNCI | TANDA_KELUAR } else { TANDA_KUNCI };
+                let kunci = tanda & TANDA_KUNCI != 0;
+                let keluar = tanda & TANDA_KELUAR != 0;
+                if tanda & !boleh != 0 || (kunci && petak != Petak::Biasa) || (keluar && y + 1 != ukuran) {
+                    return Err(tidak_sah("tanda petak tidak sah"));
+                }
+                papan.set_kunci((y, x), kunci);
+                if keluar {
+                    papan.keluar.push(x);
+                }
+            }
+            if versi >= 5 {
+                let bom = pembaca.byte()?;
+                if bom > 0 && petak != Petak::Biasa {
+                    return Err(tidak_sah("bom tidak sah"));
+                }
+                papan.set_bom((y, x), bom);
+            }
+            papan.set_petak((y, x), petak);
+        }
+    }
+    if versi >= 4 {
+        papan.bahan_tertunda.set(pembaca.angka_u32()?);
+    }
+    if versi >= 5 {
+        let jumlah = pembaca.angka()?;
+        let mut bom_tertunda = vec![];
+        for _ in 0..jumlah {
+            match pembaca.byte()? {
+                0 => return Err(tidak_sah("bom tidak sah")),
+                sisa => bom_tertunda.push(sisa),
+            }
+        }
+        papan.bom_tertunda = RefCell::new(bom_tertunda);
+    }
+    if versi >= 6 {
+        let id_berikutnya = pembaca.angka_u32()?;
+        for permen in papan.isi.iter().flatten() {
+            match pembaca.angka_u32()? {
+                id if id >= id_berikutnya => return Err(tidak_sah("identitas permen tidak sah")),
+                id => permen.id.set(id),
+            }
+        }
+        papan.id_berikutnya.set(id_berikutnya);
+    } else {
+        for (id, permen) in papan.isi.iter().flatten().enumerate() {
+            permen.id.set(id as u32);
+        }
+        papan.id_berikutnya.set((ukuran * ukuran) as u32);
+    }
+    Ok(papan)
+}
+
+// Urutannya harus sama dengan Sesi::simpanan, versi lama hanya berbeda pada isi papannya
+fn baca_sesi(pembaca: &mut Pembaca, versi: u32) -> Result<Sesi, Galat> {
+    let mut sesi = Sesi::new(baca_papan(pembaca, versi)?);
+    sesi.skor = pembaca.angka_u32()?;
+    sesi.langkah = pembaca.angka_u32()?;
+    sesi.batas_langkah = pembaca.pilihan()?.map(|n| n as u32);
+    sesi.sisa_waktu_ms = pembaca.pilihan()?;
+    let mut ambang_bintang = [0; 3];
+    for ambang in ambang_bintang.iter_mut() {
+        *ambang = pembaca.angka_u32()?;
+    }
+    sesi.ambang_bintang = Some(ambang_bintang).filter(|ambang| *ambang != [0; 3]);
+    sesi.kesulitan = match pembaca.pilihan()? {
+        Some(tingkat) if tingkat as usize >= TINGKAT_KESULITAN.len() => {
+            return Err(tidak_sah("tingkat kesulitan tidak dikenal"))
+        }
+        tingkat => tingkat.map(|n| n as usize),
+    };
+    for penguat in [Penguat::Palu, Penguat::TukarBebas, Penguat::Kocok, Penguat::TambahLangkah] {
+        sesi.persediaan.tambah(penguat, pembaca.angka_u32()?);
+    }
+    let statistik = &mut sesi.statistik;
+    for jumlah in statistik.remuk_warna.iter_mut() {
+        *jumlah = pembaca.angka_u32()?;
+    }
+    statistik.jeli_dibersihkan = pembaca.angka_u32()?;
+    statistik.bahan_terkumpul = pembaca.angka_u32()?;
+    statistik.kaskade_terbesar = pembaca.angka_u32()?;
+    statistik.spesial_dibuat = pembaca.angka_u32()?;
+    sesi.sasaran = match pembaca.byte()? {
+        0 => None,
+        1 => Some(pembaca.sasaran(0)?),
+        _ => return Err(tidak_sah("tanda pilihan tidak dikenal")),
+    };
+    if versi >= 4 {
+        sesi.jadwal_bahan = match pembaca.byte()? {
+            0 => None,
+            1 => Some(JadwalBahan { setiap: pembaca.angka_u32()?, maks: pembaca.angka_u32()? }),
+            _ => return Err(tidak_sah("tanda pilihan tidak dikenal")),
+        };
+    }
+    if versi >= 5 {
+        sesi.jadwal_bom = match pembaca.byte()? {
+            0 => None,
+            1 => Some(JadwalBom {
+                setiap: pembaca.angka_u32()?,
+                hitungan: pembaca.byte()?,
+                maks: pembaca.angka_u32()?,
+            }),
+            _ => return Err(tidak_sah("tanda pilihan tidak dikenal")),
+        };
+        let jumlah = pembaca.angka()?;
+        for _ in 0..jumlah {
+            let pos = (pembaca.angka()? as usize, pembaca.angka()? as usize);
+            if pos.0 >= sesi.papan.ukuran || pos.1 >= sesi.papan.ukuran {
+                return Err(tidak_sah("posisi bom di luar papan"));
+            }
+            sesi.bom_meledak.push(pos);
+        }
+    }
+    Ok(sesi)
+}
+
+impl Sesi {
+    /// Seluruh keadaan sesi sebagai string pendek yang aman dipakai di URL
+    ///
+    /// ### Example
+    /// ```
+    /// # use crate::permen_remuk::permenremuk::{Papan, Sesi};
+    /// let sesi = Sesi::new(Papan::dengan_benih(7, 5, 1)).dengan_batas_langkah(20);
+    /// let simpanan = sesi.simpanan();
+    /// assert!(simpanan.starts_with("6."));
+    /// let dimuat = Sesi::dari_simpanan(&simpanan).unwrap();
+    /// assert_eq!(dimuat.papan.to_string(), sesi.papan.to_string());
+    /// assert_eq!(dimuat.batas_langkah, Some(20));
+    /// ```
+    pub fn simpanan(&self) -> String {
+        let mut penulis = Penulis::default();
+        tulis_papan(&mut penulis, &self.papan);
+        penulis.angka(self.skor as u64);
+        penulis.angka(self.langkah as u64);
+        penulis.pilihan(self.batas_langkah.map(|n| n as u64));
+        penulis.pilihan(self.sisa_waktu_ms);
+        // sesi tanpa ambang bintang ditulis sebagai 0 0 0
+        for ambang in self.ambang_bintang.unwrap_or_default() {
+            penulis.angka(ambang as u64);
+        }
+        penulis.pilihan(self.kesulitan.map(|n| n as u64));
+        for penguat in [Penguat::Palu, Penguat::TukarBebas, Penguat::Kocok, Penguat::TambahLangkah] {
+            penulis.angka(self.persediaan.jumlah(penguat) as u64);
+        }
+        let statistik = &self.statistik;
+        for jumlah in statistik.remuk_warna {
+            penulis.angka(jumlah as u64);
+        }
+        penulis.angka(statistik.jeli_dibersihkan as u64);
+        penulis.angka(statistik.bahan_terkumpul as u64);
+        penulis.angka(statistik.kaskade_terbesar as u64);
+        penulis.angka(statistik.spesial_dibuat as u64);
+        match &self.sasaran {
+            Some(sasaran) => {
+                penulis.byte(1);
+                penulis.sasaran(sasaran);
+            }
+            None => penulis.byte(0),
+        }
+        match self.jadwal_bahan {
+            Some(jadwal) => {
+                penulis.byte(1);
+                penulis.angka(jadwal.setiap as u64);
+                penulis.angka(jadwal.maks as u64);
+            }
+            None => penulis.byte(0),
+        }
+        match self.jadwal_bom {
+            Some(jadwal) => {
+                penulis.byte(1);
+                penulis.angka(jadwal.setiap as u64);
+                penulis.byte(jadwal.hitungan);
+                penulis.angka(jadwal.maks as u64);
+            }
+            None => penulis.byte(0),
+        }
+        penulis.angka(self.bom_meledak.len() as u64);
+        for pos in self.bom_meledak.iter() {
+            penulis.angka(pos.0 as u64);
+            penulis.angka(pos.1 as u64);
+        }
+        format!("{}.{}", VERSI_SIMPANAN, ke_base64(&penulis.bytes))
+    }
+
+    /// Memuat sesi dari hasil Sesi::simpanan, termasuk simpanan versi lama
+    pub fn dari_simpanan(teks: &str) -> Result<Sesi, Galat> {
+        let (versi, isi) = teks.trim().split_once('.').ok_or_else(|| tidak_sah("versi simpanan tidak ada"))?;
+        let bytes = dari_base64(isi)?;
+        let mut pembaca = Pembaca { bytes: &bytes };
+        let sesi = match versi {
+            "1" => baca_sesi(&mut pembaca, 1)?,
+            "2" => baca_sesi(&mut pembaca, 2)?,
+            "3" => baca_sesi(&mut pembaca, 3)?,
+            "4" => baca_sesi(&mut pembaca, 4)?,
+            "5" => baca_sesi(&mut pembaca, 5)?,
+            "6" => baca_sesi(&mut pembaca, 6)?,
+            _ => return Err(tidak_sah("versi simpanan tidak dikenal")),
+        };
+        if !pembaca.bytes.is_empty() {
+            return Err(tidak_sah("simpanan punya isi berlebih"));
+        }
+        Ok(sesi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_bolak_balik() {
+        for panjang in 0..10 {
+            let bytes: Vec<u8> = (0..panjang).map(|i| (i * 97 + 200) as u8).collect();
+            let teks = ke_base64(&bytes);
+            assert!(teks.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+            assert_eq!(dari_base64(&teks), Ok(bytes));
+        }
+        assert_eq!(ke_base64(b"Man"), "TWFu");
+        assert!(dari_base64("TWFuT").is_err());
+        assert!(dari_base64("TW+u").is_err());
+    }
+
+    #[test]
+    fn simpanan_lanjut_sama() {
+        let mut papan = Papan::dengan_benih(8, 5, 7);
+        papan.set_petak((0, 0), Petak::Penghalang(3));
+        papan.set_jeli((3, 3), 2);
+        papan.set_jeli((6, 1), 1);
+        papan.set_petak((7, 7), Petak::Kosong);
+        papan.set_petak((7, 0), Petak::Cokelat);
+        papan.set_kunci((5, 2), true);
+        papan.keluar = vec![1, 6];
+        papan.set_jenis((4, 4), Jenis::Bungkus);
+        let mut sesi = Sesi::tanpa_akhir(papan).dengan_ambang_bintang([10, 20, 30]);
+        sesi.batas_langkah = Some(30);
+        sesi.skor = 1234;
+        sesi.langkah = 4;
+        sesi.statistik.remuk_warna[2] = 17;
+        sesi.persediaan.tambah(Penguat::Palu, 2);
+        sesi.persediaan.tambah(Penguat::Kocok, 1);
+        sesi.jadwal_bahan = Some(JadwalBahan { setiap: 3, maks: 2 });
+        sesi.papan.set_jenis((2, 6), Jenis::Bahan);
+        sesi.papan.bahan_tertunda.set(2);
+        sesi.papan.set_bom((1, 3), 4);
+        sesi.papan.bom_tertunda.borrow_mut().push(6);
+        sesi.jadwal_bom = Some(JadwalBom { setiap: 5, hitungan: 6, maks: 1 });
+        sesi.sasaran = Some(Sasaran::Dan(vec![
+            Sasaran::Skor(5000),
+            Sasaran::Atau(vec![Sasaran::RemukanWarna(Warna::Nila, 30), Sasaran::BersihkanJeli(3)]),
+        ]));
+
+        let simpanan = sesi.simpanan();
+        let mut dimuat = Sesi::dari_simpanan(&simpanan).unwrap();
+        assert_eq!(dimuat.simpanan(), simpanan);
+        assert_eq!(format!("{:#}", dimuat.papan), format!("{:#}", sesi.papan));
+        assert_eq!(dimuat.papan.palet, sesi.papan.palet);
+        assert_eq!((dimuat.skor, dimuat.langkah, dimuat.batas_langkah), (1234, 4, Some(30)));
+        assert_eq!(dimuat.ambang_bintang, Some([10, 20, 30]));
+        assert_eq!(dimuat.kesulitan, sesi.kesulitan);
+        assert_eq!(dimuat.statistik, sesi.statistik);
+        assert_eq!(dimuat.persediaan, sesi.persediaan);
+        assert_eq!(dimuat.sasaran, sesi.sasaran);
+        assert_eq!(dimuat.jadwal_bahan, sesi.jadwal_bahan);
+        assert_eq!((dimuat.papan.keluar.clone(), dimuat.papan.bahan_tertunda.get()), (vec![1, 6], 2));
+        assert_eq!(dimuat.jadwal_bom, sesi.jadwal_bom);
+        assert_eq!((dimuat.papan.get_bom((1, 3)), dimuat.papan.bom_tertunda.borrow().clone()), (4, vec![6]));
+
+        // permen yang muncul setelah dimuat sama persis
+        for sesi in [&mut sesi, &mut dimuat] {
+            sesi.pakai_palu((3, 3)).unwrap();
+            sesi.pakai_kocok().unwrap();
+            sesi.pakai_palu((5, 2)).unwrap();
+        }
+        assert_eq!(dimuat.simpanan(), sesi.simpanan());
+    }
+
+    #[test]
+    fn simpanan_identitas_permen() {
+        let mut sesi = Sesi::new(Papan::dengan_benih(7, 5, 4));
+        sesi.persediaan.tambah(Penguat::Palu, 1);
+        sesi.pakai_palu((3, 3)).unwrap();
+        let id_awal = sesi.papan.semua_id();
+        assert!(id_awal.iter().any(|id| *id >= 49));
+
+        let dimuat = Sesi::dari_simpanan(&sesi.simpanan()).unwrap();
+        assert_eq!(dimuat.papan.semua_id(), id_awal);
+        assert_eq!(dimuat.papan.id_berikutnya.get(), sesi.papan.id_berikutnya.get());
+    }
+
+    #[test]
+    fn simpanan_versi_lama() {
+        let sesi = Sesi::new(Papan::dengan_benih(6, 5, 9)).dengan_batas_langkah(12);
+        let simpanan = sesi.simpanan();
+        let (_, isi) = simpanan.split_once('.').unwrap();
+
+        // versi lama sama seperti versi terbaru tanpa identitas permen, lalu tanpa byte di ujung papan
+        // dan ujung sesi (masing-masing satu byte di sini): versi 4 tanpa bom tertunda, jadwal bom, dan
+        // bom meledak, versi sebelumnya juga tanpa bahan tertunda dan jadwal bahan. Byte terakhir di
+        // setiap petak juga hilang: versi 1 tanpa jeli dan seterusnya sampai versi 4 tanpa bom
+        let mut penulis = Penulis::default();
+        tulis_papan(&mut penulis, &sesi.papan);
+        let mut id = Penulis::default();
+        tulis_id(&mut id, &sesi.papan);
+        let bytes = dari_base64(isi).unwrap();
+        let (papan, sisa) = bytes.split_at(penulis.bytes.len());
+        let (papan, ekor) = papan.split_at(papan.len() - id.bytes.len() - 2);
+        let (kepala, petak) = papan.split_at(papan.len() - 6 * 6 * KODE_PER_PETAK);
+        let bentuk = [(1, 4, 0, 3), (2, 5, 0, 3), (3, 6, 0, 3), (4, 6, 1, 2), (5, 7, 2, 0)];
+        for (versi, panjang, ekor_papan, ekor_sesi) in bentuk {
+            let mut lama = kepala.to_vec();
+            for kode in petak.chunks(KODE_PER_PETAK) {
+                lama.extend_from_slice(&kode[..panjang]);
+            }
+            lama.extend_from_slice(&ekor[..ekor_papan]);
+            lama.extend_from_slice(&sisa[..sisa.len() - ekor_sesi]);
+
+            let dimuat = Sesi::dari_simpanan(&format!("{}.{}", versi, ke_base64(&lama))).unwrap();
+            assert_eq!(format!("{:#}", dimuat.papan), format!("{:#}", sesi.papan));
+            assert_eq!(dimuat.papan.sisa_jeli(), 0);
+            assert_eq!(dimuat.papan.semua_id(), (0..36).collect::<Vec<u32>>());
+            assert_eq!(dimuat.batas_langkah, Some(12));
+            assert_eq!(dimuat.simpanan(), sesi.simpanan());
+        }
+    }
+
+    #[test]
+    fn simpanan_galat() {
+        let simpanan = Sesi::new(Papan::dengan_benih(5, 4, 3)).simpanan();
+        let (_, isi) = simpanan.split_once('.').unwrap();
+
+        assert_eq!(Sesi::dari_simpanan(&format!("9.{}", isi)).unwrap_err(), tidak_sah("versi simpanan tidak dikenal"));
+        assert_eq!(Sesi::dari_simpanan(isi).unwrap_err(), tidak_sah("versi simpanan tidak ada"));
+        assert_eq!(Sesi::dari_simpanan(&simpanan[..simpanan.len() - 4]).unwrap_err(), tidak_sah("simpanan terpotong"));
+        assert!(Sesi::dari_simpanan(&format!("{}AAAA", simpanan)).is_err());
+        assert!(Sesi::dari_simpanan("1.!!!!").is_err());
+    }
+}